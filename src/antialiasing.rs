@@ -0,0 +1,154 @@
+use crate::camera::Camera;
+use crate::color::HdrColor;
+use crate::framebuffer::Framebuffer;
+use crate::render::Renderer;
+use crate::scene::Scene;
+
+/// Máximo de muestras por píxel en MSAA
+pub const MAX_SAMPLES: usize = 8;
+
+/// Posiciones de muestra dentro del píxel, en [0, 1)
+const PATTERN_1: [(f32, f32); 1] = [(0.5, 0.5)];
+const PATTERN_2: [(f32, f32); 2] = [(0.75, 0.75), (0.25, 0.25)];
+const PATTERN_4: [(f32, f32); 4] = [(0.375, 0.125), (0.875, 0.375), (0.125, 0.625), (0.625, 0.875)];
+const PATTERN_8: [(f32, f32); 8] = [
+    (0.5625, 0.3125), (0.4375, 0.6875), (0.8125, 0.5625), (0.3125, 0.1875),
+    (0.1875, 0.8125), (0.0625, 0.4375), (0.6875, 0.9375), (0.9375, 0.0625),
+];
+
+/// Patrón de muestras rotado (el mismo que usan las GPUs) para 1, 2, 4 u 8 muestras
+pub fn sample_pattern(samples: usize) -> &'static [(f32, f32)] {
+    match samples {
+        0 | 1 => &PATTERN_1,
+        2 | 3 => &PATTERN_2,
+        4..=7 => &PATTERN_4,
+        _ => &PATTERN_8,
+    }
+}
+
+/// Modo de antialiasing de la escena
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AntiAliasing {
+    Off,
+    /// Renderiza a N× por eje y promedia bloques de N×N
+    Ssaa(usize),
+    /// N muestras de cobertura y profundidad por píxel, un sombreado por píxel
+    Msaa(usize),
+}
+
+impl AntiAliasing {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" | "off" => Some(AntiAliasing::Off),
+            "ssaa2" => Some(AntiAliasing::Ssaa(2)),
+            "ssaa3" => Some(AntiAliasing::Ssaa(3)),
+            "ssaa4" => Some(AntiAliasing::Ssaa(4)),
+            "msaa2" => Some(AntiAliasing::Msaa(2)),
+            "msaa4" => Some(AntiAliasing::Msaa(4)),
+            "msaa8" => Some(AntiAliasing::Msaa(8)),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            AntiAliasing::Off => "sin AA".to_string(),
+            AntiAliasing::Ssaa(factor) => format!("SSAA {}x", factor * factor),
+            AntiAliasing::Msaa(samples) => format!("MSAA {}x", samples),
+        }
+    }
+
+    /// Siguiente modo al alternar con una tecla: sin AA → MSAA 4x → SSAA 4x
+    pub fn next(self) -> Self {
+        match self {
+            AntiAliasing::Off => AntiAliasing::Msaa(4),
+            AntiAliasing::Msaa(_) => AntiAliasing::Ssaa(2),
+            AntiAliasing::Ssaa(_) => AntiAliasing::Off,
+        }
+    }
+
+    /// Muestras por píxel que debe usar el rasterizador
+    pub fn msaa_samples(&self) -> usize {
+        match self {
+            AntiAliasing::Msaa(samples) => (*samples).clamp(1, MAX_SAMPLES),
+            _ => 1,
+        }
+    }
+}
+
+/// Dibuja la escena con el modo de antialiasing elegido. Para SSAA mantiene
+/// un framebuffer interno más grande que se reduce al de salida.
+pub struct AntiAliaser {
+    pub mode: AntiAliasing,
+    supersampled: Option<Framebuffer>,
+}
+
+impl AntiAliaser {
+    pub fn new(mode: AntiAliasing) -> Self {
+        AntiAliaser {
+            mode,
+            supersampled: None,
+        }
+    }
+
+    /// Equivale a `framebuffer.clear()` + `scene.render(...)` con antialiasing
+    pub fn render(
+        &mut self,
+        scene: &Scene,
+        renderer: &mut Renderer,
+        framebuffer: &mut Framebuffer,
+        camera: &Camera,
+        time: f32,
+    ) {
+        renderer.samples = self.mode.msaa_samples();
+        renderer.pixel_scale = renderer.render_scale;
+
+        let factor = match self.mode {
+            AntiAliasing::Ssaa(factor) if factor > 1 => factor,
+            _ => {
+                self.supersampled = None;
+                framebuffer.clear();
+                scene.render(renderer, framebuffer, camera, time);
+                return;
+            }
+        };
+
+        renderer.pixel_scale = renderer.render_scale * factor as f32;
+        let width = framebuffer.width * factor;
+        let height = framebuffer.height * factor;
+        let target = match &mut self.supersampled {
+            Some(target) if target.width == width && target.height == height => target,
+            slot => slot.insert(Framebuffer::new(width, height)),
+        };
+
+        target.set_background_color(framebuffer.background_color);
+        target.clear();
+        scene.render(renderer, target, camera, time);
+        downsample(target, framebuffer, factor);
+    }
+}
+
+/// Promedia bloques de `factor`×`factor` del buffer HDR grande en el de salida
+/// (la profundidad se queda con la más cercana del bloque)
+fn downsample(source: &Framebuffer, target: &mut Framebuffer, factor: usize) {
+    let weight = 1.0 / (factor * factor) as f32;
+
+    for y in 0..target.height {
+        for x in 0..target.width {
+            let mut color = HdrColor::new(0.0, 0.0, 0.0);
+            let mut depth = f32::INFINITY;
+
+            for sy in y * factor..(y + 1) * factor {
+                let row = sy * source.width;
+                for sx in x * factor..(x + 1) * factor {
+                    color = color + source.hdr[row + sx];
+                    depth = depth.min(source.zbuffer[row + sx]);
+                }
+            }
+
+            let index = y * target.width + x;
+            target.hdr[index] = color * weight;
+            target.zbuffer[index] = depth;
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::color::Color;
+use crate::image_io::write_png_rgba;
+use crate::shaders::static_layers;
+use crate::texture::{Encoding, Texture};
+
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+/// Versión de las capas estáticas de los shaders. Hay que subirla al cambiar
+/// algún `*_static_layers`, para que no se usen mapas viejos de la caché.
+pub const BAKE_VERSION: u32 = 1;
+
+/// Ancho por defecto de los mapas horneados (el alto es la mitad)
+pub const DEFAULT_BAKE_WIDTH: usize = 1024;
+
+/// Evalúa las capas estáticas de `shader_type` en cada texel de un mapa
+/// equirectangular de `width`×`width/2`, repartiendo las filas entre
+/// `threads` hilos. Devuelve bytes RGBA: el color en sRGB, como lo usan los
+/// shaders, y la máscara en el alfa. Se cargan con `Encoding::Raw`, así se
+/// filtran en el mismo espacio en que los shaders mezclan sus capas. `None` si el shader no tiene capas estáticas.
+pub fn bake(shader_type: &str, width: usize, threads: usize) -> Option<Vec<u8>> {
+    let layers = static_layers(shader_type)?;
+
+    let height = (width / 2).max(1);
+    let mut rgba = vec![0; width * height * 4];
+    let rows = Mutex::new(rgba.chunks_mut(width * 4).enumerate());
+
+    let work = || loop {
+        let Some((y, row)) = rows.lock().unwrap().next() else { break };
+        for (x, texel) in row.chunks_mut(4).enumerate() {
+            let direction = direction_from_uv((x as f32 + 0.5) / width as f32, (y as f32 + 0.5) / height as f32);
+            let texel_layers = layers(&direction);
+            let Color { r, g, b, .. } = texel_layers.color;
+            texel.copy_from_slice(&[r, g, b, (texel_layers.mask.clamp(0.0, 1.0) * 255.0 + 0.5) as u8]);
+        }
+    };
+
+    if threads <= 1 {
+        work();
+    } else {
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(work);
+            }
+        });
+    }
+
+    Some(rgba)
+}
+
+/// Mapa horneado de `shader_type`: de `cache_dir` si ya está guardado con esta
+/// resolución y versión, o recién horneado (y guardado ahí, si hay caché).
+/// Devuelve también si salió de la caché.
+pub fn load_or_bake(
+    shader_type: &str,
+    width: usize,
+    cache_dir: Option<&Path>,
+    threads: usize,
+) -> Result<Option<(Texture, bool)>, String> {
+    let cached = cache_dir.map(|dir| cache_path(dir, shader_type, width));
+    if let Some(path) = cached.as_ref().filter(|path| path.exists()) {
+        return Texture::load(path, Encoding::Raw).map(|texture| Some((texture, true)));
+    }
+
+    let Some(rgba) = bake(shader_type, width, threads) else {
+        return Ok(None);
+    };
+    let height = (width / 2).max(1);
+
+    if let Some(path) = &cached {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("No se pudo crear {}: {}", dir.display(), e))?;
+        }
+        write_png_rgba(path, &rgba, width, height)?;
+    }
+
+    Texture::from_rgba8(width, height, &rgba, Encoding::Raw).map(|texture| Some((texture, false)))
+}
+
+/// Archivo de la caché para un shader, resolución y versión de horneado
+pub fn cache_path(dir: &Path, shader_type: &str, width: usize) -> PathBuf {
+    dir.join(format!("{}_{}x{}_v{}.png", shader_type, width, (width / 2).max(1), BAKE_VERSION))
+}
+
+/// Dirección de la esfera unitaria que `get_uv_from_position` lleva a (u, v)
+fn direction_from_uv(u: f32, v: f32) -> Vec3 {
+    let latitude = (0.5 - v) * PI;
+    let longitude = (u - 0.5) * 2.0 * PI;
+    Vec3::new(
+        latitude.cos() * longitude.cos(),
+        latitude.sin(),
+        latitude.cos() * longitude.sin(),
+    )
+}
@@ -1,10 +1,17 @@
 use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
 use std::f32::consts::PI;
 
+/// Planos de recorte por defecto
+pub const DEFAULT_NEAR: f32 = 0.1;
+pub const DEFAULT_FAR: f32 = 500.0;
+
 pub struct Camera {
     pub eye: Vec3,
     pub center: Vec3,
     pub up: Vec3,
+    /// Distancias de los planos de recorte cercano y lejano
+    pub near: f32,
+    pub far: f32,
     pub has_changed: bool,
 }
 
@@ -14,42 +21,16 @@ impl Camera {
             eye,
             center,
             up,
+            near: DEFAULT_NEAR,
+            far: DEFAULT_FAR,
             has_changed: true,
         }
     }
 
-    pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
-        let forward = nalgebra_glm::normalize(&(self.center - self.eye));
-        let right = nalgebra_glm::normalize(&nalgebra_glm::cross(&forward, &self.up));
-        let up = nalgebra_glm::cross(&right, &forward);
-
-        let rotated = 
-            right * vector.x +
-            up * vector.y +
-            (-forward) * vector.z;
-
-        rotated.normalize()
-    }
-
-    pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
-        let radius_vector = self.eye - self.center;
-        let radius = radius_vector.magnitude();
-
-        let current_yaw = radius_vector.z.atan2(radius_vector.x);
-        let radius_xz = (radius_vector.x * radius_vector.x + radius_vector.z * radius_vector.z).sqrt();
-        let current_pitch = (-radius_vector.y).atan2(radius_xz);
-
-        let new_yaw = current_yaw + delta_yaw;
-        let new_pitch = (current_pitch + delta_pitch).clamp(-PI / 2.0 + 0.1, PI / 2.0 - 0.1);
-
-        let new_eye = self.center + Vec3::new(
-            radius * new_pitch.cos() * new_yaw.cos(),
-            -radius * new_pitch.sin(),
-            radius * new_pitch.cos() * new_yaw.sin()
-        );
-
-        self.eye = new_eye;
-        self.has_changed = true;
+    pub fn with_clip_planes(mut self, near: f32, far: f32) -> Self {
+        self.near = near;
+        self.far = far;
+        self
     }
 
     pub fn zoom(&mut self, delta: f32) {
@@ -63,7 +44,7 @@ impl Camera {
     }
 
     pub fn get_projection_matrix(&self, aspect: f32) -> Mat4 {
-        perspective(aspect, PI / 4.0, 1.0, 500.0)
+        perspective(aspect, PI / 4.0, self.near, self.far)
     }
 
     pub fn set_target(&mut self, target: Vec3) {
@@ -141,7 +122,6 @@ impl Camera {
     pub fn rotate_pitch(&mut self, angle: f32) {
         let forward = self.center - self.eye;
         let distance = forward.magnitude();
-        let right = forward.cross(&self.up).normalize();
         
         let cos_angle = angle.cos();
         let sin_angle = angle.sin();
@@ -154,16 +134,5 @@ impl Camera {
             self.center = self.eye + new_forward;
             self.has_changed = true;
         }
-    }    
-
-
-    pub fn warp_to(&mut self, target: Vec3, distance: f32) {
-        // Posicionar la cámara a una distancia del objetivo
-        let offset = Vec3::new(0.0, distance * 0.3, distance);
-        
-        self.eye = target + offset;
-        self.center = target;
-        self.has_changed = true;
-    }    
-
-}
\ No newline at end of file
+    }
+}
@@ -6,5 +6,4 @@ pub trait CelestialBody {
     fn get_rotation(&self) -> Vec3;
     fn get_scale(&self) -> f32;
     fn get_shader(&self) -> &str;
-    fn get_name(&self) -> &str;
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 use nalgebra_glm::Vec3;
 use crate::celestial::{CelestialBody, OrbitParams};
+use crate::texture::{SurfaceMap, Texture};
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct Planet {
@@ -22,6 +24,12 @@ pub struct Planet {
     
     // Satélites (opcional para lunas)
     pub satellites: Vec<Planet>,
+
+    // Textura equirectangular en lugar de (o mezclada con) el shader procedural
+    pub surface_map: Option<SurfaceMap>,
+
+    // Capas estáticas de su shader horneadas en un mapa (ver `bake`)
+    pub baked_layers: Option<Arc<Texture>>,
 }
 
 impl Planet {
@@ -44,6 +52,8 @@ impl Planet {
             orbit_angle: orbit.initial_angle,
             position: initial_position,
             satellites: Vec::new(),
+            surface_map: None,
+            baked_layers: None,
         }
     }
 
@@ -106,8 +116,4 @@ impl CelestialBody for Planet {
     fn get_shader(&self) -> &str {
         &self.shader_type
     }
-
-    fn get_name(&self) -> &str {
-        &self.name
-    }
 }
\ No newline at end of file
@@ -22,6 +22,40 @@ impl SolarSystem {
         SolarSystem { sun, planets }
     }
 
+    /// Escena de prueba del z-buffer: un planeta grande en el origen y una
+    /// luna quieta entre él y la cámara por defecto (0, 15, 30), con sus
+    /// superficies a 0.1 unidades. Con `--near 0.00003` la distancia a la
+    /// cámara es un millón de veces el near plane: la profundidad estándar
+    /// solo separa valores a ~1 unidad y el planeta atraviesa la luna; la
+    /// invertida los distingue sin problema.
+    pub fn depth_test() -> Self {
+        let mut sun = Planet::new("Sol", "sun", 2.0, OrbitParams::default(), 0.0);
+        sun.position = Vec3::new(30.0, 30.0, 60.0);
+
+        // Radios 5 y 0.5 (la esfera del modelo mide 0.5 × escala); la luna
+        // está inclinada hacia la cámara y corrida a un costado del centro
+        let moon_distance = 5.0 + 0.1 + 0.5;
+        let planet = Planet::new(
+            "Tierra",
+            "rocky_earth",
+            10.0,
+            OrbitParams::default(),
+            0.1,
+        ).with_satellite(
+            Planet::new(
+                "Luna",
+                "moon",
+                1.0,
+                OrbitParams::new(moon_distance, 0.0)
+                    .with_initial_angle(0.25f32.acos())
+                    .with_inclination(15.0f32.atan2(30.0)),
+                0.1,
+            )
+        );
+
+        SolarSystem { sun, planets: vec![planet] }
+    }
+
     fn create_planets() -> Vec<Planet> {
         vec![
             // MERCURIO (más cercano y rápido)
@@ -132,39 +166,10 @@ impl SolarSystem {
         self.planets.get(index)
     }
 
-    /// Obtiene un planeta mutable por índice
-    pub fn get_planet_mut(&mut self, index: usize) -> Option<&mut Planet> {
-        self.planets.get_mut(index)
-    }
-
     /// Retorna el número total de planetas
     pub fn planet_count(&self) -> usize {
         self.planets.len()
     }
-
-    /// Encuentra un planeta por nombre
-    pub fn find_planet_by_name(&self, name: &str) -> Option<(usize, &Planet)> {
-        self.planets
-            .iter()
-            .enumerate()
-            .find(|(_, planet)| planet.name == name)
-    }
-
-    /// Obtiene todas las posiciones de planetas (útil para renderizado)
-    pub fn get_all_planet_positions(&self) -> Vec<Vec3> {
-        self.planets
-            .iter()
-            .map(|planet| planet.position)
-            .collect()
-    }
-
-    /// Retorna los parámetros de órbita de todos los planetas
-    pub fn get_all_orbits(&self) -> Vec<(f32, f32)> {
-        self.planets
-            .iter()
-            .map(|planet| (planet.orbit.radius, planet.orbit.inclination))
-            .collect()
-    }
 }
 
 impl Default for SolarSystem {
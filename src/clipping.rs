@@ -0,0 +1,115 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::depth::DepthMode;
+use crate::vertex::Vertex;
+
+/// Planos del frustum en clip space (OpenGL: -w <= x, y, z <= w).
+/// Un punto está dentro del plano si `dot(plano, clip) >= 0`.
+const CLIP_PLANES: [Vec4; 6] = [
+    Vec4::new(0.0, 0.0, 1.0, 1.0),   // Near
+    Vec4::new(0.0, 0.0, -1.0, 1.0),  // Far
+    Vec4::new(1.0, 0.0, 0.0, 1.0),   // Izquierda
+    Vec4::new(-1.0, 0.0, 0.0, 1.0),  // Derecha
+    Vec4::new(0.0, 1.0, 0.0, 1.0),   // Abajo
+    Vec4::new(0.0, -1.0, 0.0, 1.0),  // Arriba
+];
+
+fn plane_distance(plane: &Vec4, vertex: &Vertex) -> f32 {
+    plane.dot(&vertex.clip_position)
+}
+
+/// Recorta un triángulo contra los seis planos del frustum (Sutherland-Hodgman).
+/// Devuelve el polígono resultante (vacío si el triángulo queda fuera).
+pub fn clip_triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Vertex> {
+    let mut inside_all = true;
+
+    for plane in &CLIP_PLANES {
+        let d1 = plane_distance(plane, v1);
+        let d2 = plane_distance(plane, v2);
+        let d3 = plane_distance(plane, v3);
+
+        // Completamente fuera de un plano: se descarta sin recortar
+        if d1 < 0.0 && d2 < 0.0 && d3 < 0.0 {
+            return Vec::new();
+        }
+        if d1 < 0.0 || d2 < 0.0 || d3 < 0.0 {
+            inside_all = false;
+        }
+    }
+
+    let mut polygon = vec![v1.clone(), v2.clone(), v3.clone()];
+    if inside_all {
+        return polygon;
+    }
+
+    for plane in &CLIP_PLANES {
+        polygon = clip_polygon_against_plane(&polygon, plane);
+        if polygon.len() < 3 {
+            return Vec::new();
+        }
+    }
+
+    polygon
+}
+
+fn clip_polygon_against_plane(polygon: &[Vertex], plane: &Vec4) -> Vec<Vertex> {
+    let mut output = Vec::with_capacity(polygon.len() + 1);
+
+    for i in 0..polygon.len() {
+        let current = &polygon[i];
+        let next = &polygon[(i + 1) % polygon.len()];
+
+        let d_current = plane_distance(plane, current);
+        let d_next = plane_distance(plane, next);
+
+        if d_current >= 0.0 {
+            output.push(current.clone());
+        }
+
+        // La arista cruza el plano: agregar el punto de intersección
+        if (d_current >= 0.0) != (d_next >= 0.0) {
+            let t = d_current / (d_current - d_next);
+            output.push(current.lerp(next, t));
+        }
+    }
+
+    output
+}
+
+/// Recorta un segmento contra los seis planos del frustum (Liang-Barsky en clip space).
+/// Devuelve `None` si el segmento queda completamente fuera.
+pub fn clip_line(v1: &Vertex, v2: &Vertex) -> Option<(Vertex, Vertex)> {
+    let mut t_start: f32 = 0.0;
+    let mut t_end: f32 = 1.0;
+
+    for plane in &CLIP_PLANES {
+        let d1 = plane_distance(plane, v1);
+        let d2 = plane_distance(plane, v2);
+
+        if d1 < 0.0 && d2 < 0.0 {
+            return None;
+        }
+        if d1 < 0.0 {
+            t_start = t_start.max(d1 / (d1 - d2));
+        } else if d2 < 0.0 {
+            t_end = t_end.min(d1 / (d1 - d2));
+        }
+    }
+
+    if t_start > t_end {
+        return None;
+    }
+
+    let start = if t_start > 0.0 { v1.lerp(v2, t_start) } else { v1.clone() };
+    let end = if t_end < 1.0 { v1.lerp(v2, t_end) } else { v2.clone() };
+    Some((start, end))
+}
+
+/// División de perspectiva y transformación de viewport de un vértice ya
+/// recortado. La z de pantalla es la profundidad que pide `depth_mode`.
+pub fn project_to_screen(vertex: &mut Vertex, viewport_matrix: &Mat4, depth_mode: DepthMode) {
+    let clip = vertex.clip_position;
+    let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    let screen = viewport_matrix * ndc;
+
+    vertex.transformed_position = Vec3::new(screen.x, screen.y, depth_mode.screen_depth(&clip));
+}
@@ -1,81 +1,170 @@
 use std::fmt;
 use std::ops::{Add, Mul};
+use std::sync::OnceLock;
 
+/// Color RGBA de 8 bits por canal. `a` es la cobertura (255 = opaco); el
+/// framebuffer solo guarda RGB y el alfa se usa al mezclar.
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
     pub b: u8,
+    pub a: u8,
 }
 
 impl Color {
     pub fn new(r: u8, g: u8, b: u8) -> Self {
-        Color { r, g, b }
+        Color { r, g, b, a: 255 }
     }
 
-    pub fn black() -> Self {
-        Color { r: 0, g: 0, b: 0 }
+    pub fn new_rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
     }
 
-    pub fn white() -> Self {
-        Color { r: 255, g: 255, b: 255 }
+    pub fn black() -> Self {
+        Color::new(0, 0, 0)
     }
 
     pub fn from_float(r: f32, g: f32, b: f32) -> Self {
-        Color {
-            r: (r.clamp(0.0, 1.0) * 255.0) as u8,
-            g: (g.clamp(0.0, 1.0) * 255.0) as u8,
-            b: (b.clamp(0.0, 1.0) * 255.0) as u8,
-        }
+        Color::new(
+            (r.clamp(0.0, 1.0) * 255.0) as u8,
+            (g.clamp(0.0, 1.0) * 255.0) as u8,
+            (b.clamp(0.0, 1.0) * 255.0) as u8,
+        )
     }
 
-    pub fn from_hex(hex: u32) -> Self {
-        let r = ((hex >> 16) & 0xFF) as u8;
-        let g = ((hex >> 8) & 0xFF) as u8;
-        let b = (hex & 0xFF) as u8;
-        Color { r, g, b }
+    /// Empaqueta RGB para el framebuffer (el alfa se descarta)
+    pub fn to_hex(self) -> u32 {
+        ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 
-    pub fn to_hex(&self) -> u32 {
-        ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
+    /// Mismo color con otra cobertura, en [0, 1]
+    pub fn with_alpha(self, alpha: f32) -> Color {
+        Color {
+            a: (alpha.clamp(0.0, 1.0) * 255.0) as u8,
+            ..self
+        }
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.a as f32 / 255.0
     }
 
     pub fn lerp(&self, other: &Color, t: f32) -> Color {
         let t = t.clamp(0.0, 1.0);
-        Color::new(
+        Color::new_rgba(
             (self.r as f32 + (other.r as f32 - self.r as f32) * t) as u8,
             (self.g as f32 + (other.g as f32 - self.g as f32) * t) as u8,
             (self.b as f32 + (other.b as f32 - self.b as f32) * t) as u8,
+            (self.a as f32 + (other.a as f32 - self.a as f32) * t) as u8,
         )
     }
 }
 
-impl Add for Color {
-    type Output = Color;
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Color({}, {}, {}, {})", self.r, self.g, self.b, self.a)
+    }
+}
+
+/// Decodifica un canal sRGB de 8 bits a intensidad lineal
+pub fn srgb_to_linear(value: u8) -> f32 {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [0.0; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let c = i as f32 / 255.0;
+            *entry = if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            };
+        }
+        table
+    });
+    table[value as usize]
+}
 
-    fn add(self, other: Color) -> Color {
-        Color {
-            r: self.r.saturating_add(other.r),
-            g: self.g.saturating_add(other.g),
-            b: self.b.saturating_add(other.b),
+/// Codifica una intensidad lineal en [0, 1] a un canal sRGB de 8 bits
+pub fn linear_to_srgb(value: f32) -> u8 {
+    let c = value.clamp(0.0, 1.0);
+    let encoded = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0 + 0.5) as u8
+}
+
+/// Color lineal en punto flotante, sin límite superior. Es lo que guarda el
+/// buffer HDR del framebuffer; el tone mapping lo lleva a `Color` al final.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HdrColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl HdrColor {
+    pub fn new(r: f32, g: f32, b: f32) -> Self {
+        HdrColor { r, g, b, a: 1.0 }
+    }
+
+    pub fn black() -> Self {
+        HdrColor::new(0.0, 0.0, 0.0)
+    }
+
+    /// Luminancia relativa (Rec. 709)
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    pub fn lerp(&self, other: &HdrColor, t: f32) -> HdrColor {
+        HdrColor {
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+            a: self.a + (other.a - self.a) * t,
         }
     }
 }
 
-impl Mul<f32> for Color {
-    type Output = Color;
+impl From<Color> for HdrColor {
+    /// Los shaders trabajan en sRGB de 8 bits: se decodifica a lineal
+    fn from(color: Color) -> Self {
+        HdrColor {
+            r: srgb_to_linear(color.r),
+            g: srgb_to_linear(color.g),
+            b: srgb_to_linear(color.b),
+            a: color.alpha(),
+        }
+    }
+}
 
-    fn mul(self, scalar: f32) -> Color {
-        Color {
-            r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
-            g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
-            b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
+impl Add for HdrColor {
+    type Output = HdrColor;
+
+    fn add(self, other: HdrColor) -> HdrColor {
+        HdrColor {
+            r: self.r + other.r,
+            g: self.g + other.g,
+            b: self.b + other.b,
+            a: self.a.max(other.a),
         }
     }
 }
 
-impl fmt::Display for Color {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Color({}, {}, {})", self.r, self.g, self.b)
+impl Mul<f32> for HdrColor {
+    type Output = HdrColor;
+
+    /// Escala la intensidad; la cobertura no cambia
+    fn mul(self, scalar: f32) -> HdrColor {
+        HdrColor {
+            r: self.r * scalar,
+            g: self.g * scalar,
+            b: self.b * scalar,
+            a: self.a,
+        }
     }
-}
\ No newline at end of file
+}
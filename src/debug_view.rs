@@ -0,0 +1,125 @@
+use crate::color::{Color, HdrColor};
+use crate::fragment::Fragment;
+use crate::framebuffer::BlendMode;
+use crate::shaders::{get_uv_from_position, Uniforms};
+
+/// Color de las aristas en la vista de alambre
+const WIREFRAME_COLOR: Color = Color { r: 80, g: 255, b: 120, a: 255 };
+
+/// Mapa de calor del overdraw: índice = fragmentos en el píxel (1, 2, 3...);
+/// a partir del último todos se ven blancos
+const OVERDRAW_PALETTE: [Color; 8] = [
+    Color { r: 0, g: 0, b: 0, a: 255 },
+    Color { r: 20, g: 40, b: 160, a: 255 },
+    Color { r: 0, g: 150, b: 200, a: 255 },
+    Color { r: 0, g: 190, b: 60, a: 255 },
+    Color { r: 230, g: 220, b: 0, a: 255 },
+    Color { r: 250, g: 130, b: 0, a: 255 },
+    Color { r: 230, g: 20, b: 20, a: 255 },
+    Color { r: 255, g: 255, b: 255, a: 255 },
+];
+
+/// Vistas de depuración del renderer. Reemplazan lo que devuelve
+/// `fragment_shader` para los triángulos (los shaders de cada cuerpo no se
+/// tocan); líneas y puntos se dibujan igual porque no pasan por él.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DebugView {
+    /// Render normal
+    Off,
+    /// Sombreado normal con las aristas de cada triángulo rasterizado encima
+    Wireframe,
+    /// Normal interpolada en espacio de mundo como RGB (`n × 0.5 + 0.5`)
+    Normals,
+    /// Profundidad lineal de vista (la `w` de clip), blanco lo más cercano
+    /// del frame y oscuro lo más lejano
+    Depth,
+    /// Coordenadas de `get_uv_from_position` como rojo (u) y verde (v)
+    Uvs,
+    /// Cantidad de fragmentos rasterizados por píxel, como mapa de calor
+    Overdraw,
+}
+
+impl DebugView {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" | "off" => Some(DebugView::Off),
+            "wireframe" => Some(DebugView::Wireframe),
+            "normals" => Some(DebugView::Normals),
+            "depth" => Some(DebugView::Depth),
+            "uv" | "uvs" => Some(DebugView::Uvs),
+            "overdraw" => Some(DebugView::Overdraw),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DebugView::Off => "normal",
+            DebugView::Wireframe => "alambre",
+            DebugView::Normals => "normales",
+            DebugView::Depth => "profundidad",
+            DebugView::Uvs => "UVs",
+            DebugView::Overdraw => "overdraw",
+        }
+    }
+
+    /// La imagen son los valores de la vista y no el render: se muestra sin
+    /// bloom ni tone mapping. El alambre es el render normal con aristas.
+    pub fn replaces_output(self) -> bool {
+        !matches!(self, DebugView::Off | DebugView::Wireframe)
+    }
+
+    /// Activa esta vista, o vuelve al render normal si ya estaba activa
+    /// (para asignar una tecla a cada una)
+    pub fn toggle(self, current: DebugView) -> DebugView {
+        if current == self { DebugView::Off } else { self }
+    }
+}
+
+/// Color de un fragmento en las vistas que reemplazan al shader. `depth_range`
+/// es la `w` mínima y máxima de los triángulos del frame. Devuelve `None` en
+/// las vistas que usan el shader normal.
+pub fn debug_shader(fragment: &Fragment, uniforms: &Uniforms, view: DebugView, depth_range: (f32, f32)) -> Option<HdrColor> {
+    let color = match view {
+        DebugView::Normals => {
+            let n = fragment.normal * 0.5 + nalgebra_glm::Vec3::new(0.5, 0.5, 0.5);
+            Color::from_float(n.x, n.y, n.z)
+        }
+        DebugView::Depth => {
+            let view_position = uniforms.view_matrix * fragment.world_position.push(1.0);
+            let (near, far) = depth_range;
+            let t = ((-view_position.z - near) / (far - near).max(1e-6)).clamp(0.0, 1.0);
+            let gray = 1.0 - 0.9 * t;
+            Color::from_float(gray, gray, gray)
+        }
+        DebugView::Uvs => {
+            let uv = get_uv_from_position(&fragment.normal);
+            Color::from_float(uv.x, uv.y, 0.0)
+        }
+        DebugView::Off | DebugView::Wireframe | DebugView::Overdraw => return None,
+    };
+
+    // Los valores se ven tal cual en pantalla (sin tone mapping ni bloom)
+    Some(HdrColor::from(color))
+}
+
+/// Mezcla una arista de `width` píxeles sobre `color` según la distancia en
+/// píxeles de la muestra al borde más cercano de su triángulo
+pub fn wireframe_overlay(color: HdrColor, edge_distance: f32, width: f32) -> HdrColor {
+    let coverage = (width * 0.5 + 0.5 - edge_distance).clamp(0.0, 1.0);
+    if coverage <= 0.0 {
+        return color;
+    }
+
+    let mut edge = HdrColor::from(WIREFRAME_COLOR);
+    edge.a = coverage;
+    let mut blended = BlendMode::Alpha.blend(edge, color);
+    blended.a = color.a;
+    blended
+}
+
+/// Color del mapa de calor para `count` fragmentos en un píxel
+pub fn overdraw_color(count: u32) -> HdrColor {
+    let index = (count as usize).min(OVERDRAW_PALETTE.len() - 1);
+    HdrColor::from(OVERDRAW_PALETTE[index])
+}
@@ -0,0 +1,50 @@
+use nalgebra_glm::Vec4;
+
+/// Qué valor de profundidad se guarda en el z-buffer por cada muestra.
+/// En todos los modos un valor menor está más cerca de la cámara.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DepthMode {
+    /// `z/w` de la proyección OpenGL, en [-1, 1]. Casi toda la precisión del
+    /// float queda pegada al near plane: con un near muy chico los cuerpos
+    /// lejanos caen en el mismo valor y parpadean (z-fighting)
+    Standard,
+    /// `1/w` en punto flotante: infinito en el ojo y tendiendo a 0 lejos, así
+    /// la precisión relativa del float se reparte pareja en toda la distancia
+    /// y no depende de near/far. Se guarda negado (negar es exacto) para que
+    /// el test de profundidad siga siendo "menor pasa"
+    ReversedZ,
+}
+
+impl DepthMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "standard" => Some(DepthMode::Standard),
+            "reversed" | "reversed-z" => Some(DepthMode::ReversedZ),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DepthMode::Standard => "z estándar",
+            DepthMode::ReversedZ => "z invertido",
+        }
+    }
+
+    /// Siguiente modo (para alternarlos con una tecla)
+    pub fn next(self) -> Self {
+        match self {
+            DepthMode::Standard => DepthMode::ReversedZ,
+            DepthMode::ReversedZ => DepthMode::Standard,
+        }
+    }
+
+    /// Profundidad de pantalla de un vértice ya recortado. Ambas son afines en
+    /// espacio de pantalla, así que se interpolan linealmente al rasterizar.
+    pub fn screen_depth(self, clip: &Vec4) -> f32 {
+        match self {
+            DepthMode::Standard => clip.z / clip.w,
+            DepthMode::ReversedZ => -1.0 / clip.w,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use crate::color::HdrColor;
+use crate::framebuffer::Framebuffer;
+
+/// Pesos de un Gaussiano de 9 muestras (centro + 4 a cada lado)
+const GAUSSIAN_WEIGHTS: [f32; 5] = [0.227_027, 0.194_594_6, 0.121_621_6, 0.054_054, 0.016_216];
+
+/// Post-proceso de resplandor sobre el buffer HDR. Trabaja con cualquier
+/// píxel cuya luminancia supere `threshold`, así que sirve igual para el sol,
+/// el warp o cualquier shader que emita por encima del blanco.
+#[derive(Clone, Copy, Debug)]
+pub struct Bloom {
+    pub enabled: bool,
+    /// Luminancia lineal a partir de la cual un píxel brilla
+    pub threshold: f32,
+    /// Cuánto del resplandor se suma a la imagen
+    pub intensity: f32,
+    /// Niveles de mip (cada uno a la mitad de resolución del anterior)
+    pub levels: usize,
+}
+
+/// Imagen HDR auxiliar para los niveles de la cadena de mips
+struct Level {
+    width: usize,
+    height: usize,
+    pixels: Vec<HdrColor>,
+}
+
+impl Level {
+    fn new(width: usize, height: usize) -> Self {
+        Level {
+            width,
+            height,
+            pixels: vec![HdrColor::black(); width * height],
+        }
+    }
+
+    fn get(&self, x: isize, y: isize) -> HdrColor {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    /// Promedio de bloques de 2x2
+    fn downsample(&self) -> Level {
+        let mut half = Level::new((self.width / 2).max(1), (self.height / 2).max(1));
+        for y in 0..half.height {
+            for x in 0..half.width {
+                let (sx, sy) = (x as isize * 2, y as isize * 2);
+                let sum = self.get(sx, sy) + self.get(sx + 1, sy) + self.get(sx, sy + 1) + self.get(sx + 1, sy + 1);
+                half.pixels[y * half.width + x] = sum * 0.25;
+            }
+        }
+        half
+    }
+
+    /// Desenfoque Gaussiano separable: una pasada horizontal y una vertical
+    fn blur(&mut self) {
+        let horizontal = self.convolve(1, 0);
+        self.pixels = horizontal.pixels;
+        let vertical = self.convolve(0, 1);
+        self.pixels = vertical.pixels;
+    }
+
+    fn convolve(&self, dx: isize, dy: isize) -> Level {
+        let mut out = Level::new(self.width, self.height);
+        for y in 0..self.height as isize {
+            for x in 0..self.width as isize {
+                let mut sum = self.get(x, y) * GAUSSIAN_WEIGHTS[0];
+                for (i, weight) in GAUSSIAN_WEIGHTS.iter().enumerate().skip(1) {
+                    let offset = i as isize;
+                    sum = sum
+                        + self.get(x + dx * offset, y + dy * offset) * *weight
+                        + self.get(x - dx * offset, y - dy * offset) * *weight;
+                }
+                out.pixels[y as usize * self.width + x as usize] = sum;
+            }
+        }
+        out
+    }
+
+    /// Muestreo bilineal con coordenadas normalizadas al centro del píxel
+    fn sample(&self, u: f32, v: f32) -> HdrColor {
+        let fx = u * self.width as f32 - 0.5;
+        let fy = v * self.height as f32 - 0.5;
+        let x0 = fx.floor();
+        let y0 = fy.floor();
+        let tx = fx - x0;
+        let ty = fy - y0;
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let top = self.get(x0, y0).lerp(&self.get(x0 + 1, y0), tx);
+        let bottom = self.get(x0, y0 + 1).lerp(&self.get(x0 + 1, y0 + 1), tx);
+        top.lerp(&bottom, ty)
+    }
+}
+
+impl Bloom {
+    pub fn new(threshold: f32, intensity: f32) -> Self {
+        Bloom {
+            enabled: true,
+            threshold,
+            intensity,
+            levels: 4,
+        }
+    }
+
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        self.apply_to(&mut framebuffer.hdr, framebuffer.width, framebuffer.height);
+    }
+
+    /// Extrae lo brillante, lo desenfoca en varios niveles y lo suma de vuelta
+    pub fn apply_to(&self, hdr: &mut [HdrColor], width: usize, height: usize) {
+        if !self.enabled || self.intensity <= 0.0 || self.levels == 0 || width < 2 || height < 2 {
+            return;
+        }
+
+        // 1. Extraer píxeles brillantes (conservando su color)
+        let mut bright = Level::new(width, height);
+        let mut any_bright = false;
+        for (out, color) in bright.pixels.iter_mut().zip(hdr.iter()) {
+            let luminance = color.luminance();
+            if luminance > self.threshold {
+                *out = *color * ((luminance - self.threshold) / luminance);
+                any_bright = true;
+            }
+        }
+        if !any_bright {
+            return;
+        }
+
+        // 2. Cadena de mips, cada nivel desenfocado: radios cada vez mayores
+        let mut levels: Vec<Level> = Vec::with_capacity(self.levels);
+        let mut current = bright.downsample();
+        for _ in 0..self.levels {
+            current.blur();
+            let next = current.downsample();
+            levels.push(current);
+            if next.width < 2 || next.height < 2 {
+                break;
+            }
+            current = next;
+        }
+
+        // 3. Componer: sumar todos los niveles reescalados a pantalla
+        let weight = self.intensity / levels.len() as f32;
+        for y in 0..height {
+            let v = (y as f32 + 0.5) / height as f32;
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let glow = levels
+                    .iter()
+                    .fold(HdrColor::new(0.0, 0.0, 0.0), |sum, level| sum + level.sample(u, v));
+
+                let index = y * width + x;
+                hdr[index] = hdr[index] + glow * weight;
+            }
+        }
+    }
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom::new(1.0, 0.8)
+    }
+}
@@ -1,7 +1,11 @@
+pub mod bloom;
 pub mod orbit_renderer;
 pub mod skybox;
+pub mod starfield;
 pub mod warp_effect;
 
+pub use bloom::Bloom;
 pub use orbit_renderer::create_orbit_lines;
 pub use skybox::create_skybox_sphere;
-pub use warp_effect::{WarpEffect, WarpState};
+pub use starfield::create_starfield;
+pub use warp_effect::WarpEffect;
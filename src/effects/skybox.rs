@@ -2,7 +2,10 @@ use crate::vertex::Vertex;
 use nalgebra_glm::{Vec3, Vec2};
 use std::f32::consts::PI;
 
-/// Crea una esfera grande invertida para el skybox
+/// Crea una esfera grande invertida para el skybox.
+/// Los triángulos quedan antihorarios vistos desde dentro, así que basta con
+/// `DrawState::default()`; si se usa una esfera normal (como `sphere.obj`)
+/// hay que dibujarla con `CullMode::Front`.
 pub fn create_skybox_sphere(radius: f32, stacks: u32, slices: u32) -> Vec<Vertex> {
     let mut vertices = Vec::new();
     
@@ -0,0 +1,42 @@
+use crate::vertex::Vertex;
+use crate::color::Color;
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+/// Tonos de estrella, de las más frías (rojizas) a las más calientes (azuladas)
+const STAR_TINTS: [Color; 4] = [
+    Color { r: 255, g: 214, b: 170, a: 255 },
+    Color { r: 255, g: 244, b: 232, a: 255 },
+    Color { r: 236, g: 240, b: 255, a: 255 },
+    Color { r: 190, g: 210, b: 255, a: 255 },
+];
+
+/// Crea `count` estrellas repartidas uniformemente sobre una esfera de `radius`
+/// centrada en el origen, para dibujar con `Renderer::draw_points` trasladadas
+/// a la posición de la cámara (así nunca se alcanzan).
+/// El brillo de cada estrella va en el alfa de su color, pensado para
+/// `BlendMode::Additive`. La misma `seed` produce siempre el mismo cielo.
+pub fn create_starfield(count: usize, radius: f32, seed: u32) -> Vec<Vertex> {
+    let mut state = seed.wrapping_mul(747_796_405).wrapping_add(2_891_336_453);
+    let mut random = move || {
+        // Generador congruencial lineal: suficiente para repartir estrellas
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 24) as f32
+    };
+
+    (0..count)
+        .map(|_| {
+            // Punto uniforme en la esfera: altura uniforme y ángulo uniforme
+            let y = random() * 2.0 - 1.0;
+            let theta = random() * 2.0 * PI;
+            let ring = (1.0 - y * y).sqrt();
+            let position = Vec3::new(ring * theta.cos(), y, ring * theta.sin()) * radius;
+
+            // Pocas estrellas brillantes y muchas tenues
+            let brightness = 0.15 + 0.85 * random().powi(3);
+            let tint = STAR_TINTS[(random() * STAR_TINTS.len() as f32) as usize % STAR_TINTS.len()];
+
+            Vertex::new_with_color(position, tint.with_alpha(brightness))
+        })
+        .collect()
+}
@@ -1,33 +1,27 @@
-use nalgebra_glm::Vec2;
+use nalgebra_glm::{Vec2, Vec3};
 use crate::color::Color;
+use crate::varyings::{self, Varyings};
 
 #[derive(Clone, Debug)]
 pub struct Fragment {
-    pub position: Vec2,
     pub color: Color,
-    pub depth: f32,
-    pub normal: nalgebra_glm::Vec3,
-    pub intensity: f32,
+    pub normal: Vec3,
+    /// Posición en el mundo, interpolada con corrección de perspectiva
+    pub world_position: Vec3,
+    pub tex_coords: Vec2,
+    pub varyings: Varyings,
 }
 
 impl Fragment {
-    pub fn new(x: f32, y: f32, color: Color, depth: f32) -> Self {
+    /// Construye un fragmento a partir de los varyings interpolados,
+    /// desempacando la distribución estándar de `vertex_shader`
+    pub fn from_varyings(varyings: Varyings) -> Self {
         Fragment {
-            position: Vec2::new(x, y),
-            color,
-            depth,
-            normal: nalgebra_glm::Vec3::new(0.0, 0.0, 1.0),
-            intensity: 1.0,
+            color: varyings.get_color(varyings::COLOR),
+            normal: varyings.get_vec3(varyings::NORMAL).normalize(),
+            world_position: varyings.get_vec3(varyings::WORLD_POSITION),
+            tex_coords: varyings.get_vec2(varyings::TEX_COORDS),
+            varyings,
         }
     }
-
-    pub fn new_with_normal(x: f32, y: f32, color: Color, depth: f32, normal: nalgebra_glm::Vec3, intensity: f32) -> Self {
-        Fragment {
-            position: Vec2::new(x, y),
-            color,
-            depth,
-            normal,
-            intensity,
-        }
-    }
-}
\ No newline at end of file
+}
@@ -1,15 +1,65 @@
-use crate::color::Color;
+use crate::color::{Color, HdrColor};
+use crate::tonemap::ToneMapper;
 
-pub const SCREEN_WIDTH: usize = 800;
-pub const SCREEN_HEIGHT: usize = 600;
+/// Tamaño interno de render para una ventana de `width`×`height` con una
+/// escala de render (1 = resolución nativa, 0.5 = la mitad por eje)
+pub fn scaled_size(width: usize, height: usize, scale: f32) -> (usize, usize) {
+    (
+        ((width as f32 * scale).round() as usize).max(1),
+        ((height as f32 * scale).round() as usize).max(1),
+    )
+}
+
+/// Cómo se combina un fragmento con lo que ya hay en el framebuffer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendMode {
+    /// Reemplaza el color y escribe profundidad
+    Opaque,
+    /// `src * a + dst * (1 - a)`
+    Alpha,
+    /// `dst + src * a`, para brillos y resplandores
+    Additive,
+    /// `dst * src`, atenuado por la cobertura; para tintes y sombras.
+    /// Ningún objeto de la escena lo usa todavía.
+    #[cfg_attr(not(test), allow(dead_code))]
+    Multiply,
+}
+
+impl BlendMode {
+    /// Mezcla `src` (con su alfa) sobre `dst`, en espacio lineal
+    pub fn blend(self, src: HdrColor, dst: HdrColor) -> HdrColor {
+        let a = src.a;
+        let channel = |s: f32, d: f32| -> f32 {
+            match self {
+                BlendMode::Opaque => s,
+                BlendMode::Alpha => s * a + d * (1.0 - a),
+                BlendMode::Additive => d + s * a,
+                BlendMode::Multiply => d * (1.0 - a + a * s),
+            }
+        };
+
+        HdrColor::new(
+            channel(src.r, dst.r),
+            channel(src.g, dst.g),
+            channel(src.b, dst.b),
+        )
+    }
+
+    /// Los modos con mezcla no escriben profundidad y van en la pasada transparente
+    pub fn is_transparent(self) -> bool {
+        self != BlendMode::Opaque
+    }
+}
 
+/// `hdr` es el color lineal en punto flotante donde se dibuja la escena;
+/// `buffer` (0x00RRGGBB) solo se llena en `resolve`, al final del frame.
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>,
+    pub hdr: Vec<HdrColor>,
     pub zbuffer: Vec<f32>,
     pub background_color: Color,
-    pub current_color: Color,
 }
 
 impl Framebuffer {
@@ -18,33 +68,84 @@ impl Framebuffer {
             width,
             height,
             buffer: vec![0; width * height],
+            hdr: vec![HdrColor::black(); width * height],
             zbuffer: vec![1000.0; width * height],
             background_color: Color::black(),
-            current_color: Color::new(255, 255, 255),
         }
     }
 
+    /// Cambia el tamaño de todos los buffers (por ejemplo al redimensionar la
+    /// ventana). El contenido se pierde; el siguiente `clear` lo repone.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        if width == self.width && height == self.height {
+            return;
+        }
+
+        self.width = width;
+        self.height = height;
+        self.buffer = vec![0; width * height];
+        self.hdr = vec![HdrColor::black(); width * height];
+        self.zbuffer = vec![1000.0; width * height];
+    }
+
     pub fn clear(&mut self) {
         let bg_color = self.background_color.to_hex();
         for pixel in self.buffer.iter_mut() {
             *pixel = bg_color;
         }
+        let bg_linear = HdrColor::from(self.background_color);
+        for pixel in self.hdr.iter_mut() {
+            *pixel = bg_linear;
+        }
         for depth in self.zbuffer.iter_mut() {
             *depth = 1000.0;
         }
     }
 
-    pub fn set_current_color(&mut self, color: Color) {
-        self.current_color = color;
+    /// Pasa el buffer HDR por el tone mapping y llena `buffer` para la ventana
+    pub fn resolve(&mut self, tone_mapper: &ToneMapper) {
+        for (pixel, color) in self.buffer.iter_mut().zip(&self.hdr) {
+            *pixel = tone_mapper.map(*color).to_hex();
+        }
     }
 
-    pub fn point(&mut self, x: usize, y: usize, depth: f32) {
-        if x < self.width && y < self.height {
-            let index = y * self.width + x;
-            
-            if depth < self.zbuffer[index] {
-                self.buffer[index] = self.current_color.to_hex();
-                self.zbuffer[index] = depth;
+    /// Escala `buffer` (ya resuelto) a `width`×`height` con filtrado bilineal,
+    /// para mostrar un render a menor resolución en una ventana más grande
+    pub fn upscale_into(&self, target: &mut Vec<u32>, width: usize, height: usize) {
+        target.resize(width * height, 0);
+
+        let channel = |pixel: u32, shift: u32| ((pixel >> shift) & 0xFF) as f32;
+        let scale_x = self.width as f32 / width as f32;
+        let scale_y = self.height as f32 / height as f32;
+
+        for y in 0..height {
+            // Centros de píxel alineados entre ambas resoluciones
+            let sy = ((y as f32 + 0.5) * scale_y - 0.5).clamp(0.0, (self.height - 1) as f32);
+            let y0 = sy.floor() as usize;
+            let y1 = (y0 + 1).min(self.height - 1);
+            let ty = sy - y0 as f32;
+
+            for x in 0..width {
+                let sx = ((x as f32 + 0.5) * scale_x - 0.5).clamp(0.0, (self.width - 1) as f32);
+                let x0 = sx.floor() as usize;
+                let x1 = (x0 + 1).min(self.width - 1);
+                let tx = sx - x0 as f32;
+
+                let corners = [
+                    self.buffer[y0 * self.width + x0],
+                    self.buffer[y0 * self.width + x1],
+                    self.buffer[y1 * self.width + x0],
+                    self.buffer[y1 * self.width + x1],
+                ];
+
+                let mut pixel = 0u32;
+                for shift in [16, 8, 0] {
+                    let top = channel(corners[0], shift) * (1.0 - tx) + channel(corners[1], shift) * tx;
+                    let bottom = channel(corners[2], shift) * (1.0 - tx) + channel(corners[3], shift) * tx;
+                    let value = top * (1.0 - ty) + bottom * ty;
+                    pixel |= (value.round() as u32).min(255) << shift;
+                }
+                target[y * width + x] = pixel;
             }
         }
     }
@@ -52,4 +153,38 @@ impl Framebuffer {
     pub fn set_background_color(&mut self, color: Color) {
         self.background_color = color;
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: HdrColor, expected: HdrColor) {
+        for (a, e) in [(actual.r, expected.r), (actual.g, expected.g), (actual.b, expected.b)] {
+            assert!((a - e).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn every_blend_mode_combines_source_and_destination() {
+        let src = HdrColor { a: 0.25, ..HdrColor::new(0.8, 0.4, 2.0) };
+        let dst = HdrColor::new(0.2, 0.6, 0.5);
+
+        assert_close(BlendMode::Opaque.blend(src, dst), HdrColor::new(0.8, 0.4, 2.0));
+        assert_close(BlendMode::Alpha.blend(src, dst), HdrColor::new(0.35, 0.55, 0.875));
+        assert_close(BlendMode::Additive.blend(src, dst), HdrColor::new(0.4, 0.7, 1.0));
+        assert_close(BlendMode::Multiply.blend(src, dst), HdrColor::new(0.19, 0.51, 0.625));
+
+        // Con cobertura completa la mezcla alfa reemplaza y la multiplicativa multiplica
+        let solid = HdrColor::new(0.8, 0.4, 2.0);
+        assert_close(BlendMode::Alpha.blend(solid, dst), solid);
+        assert_close(BlendMode::Multiply.blend(solid, dst), HdrColor::new(0.16, 0.24, 1.0));
+
+        // Sin cobertura ningún modo transparente toca el destino
+        let clear = HdrColor { a: 0.0, ..solid };
+        for mode in [BlendMode::Alpha, BlendMode::Additive, BlendMode::Multiply] {
+            assert_close(mode.blend(clear, dst), dst);
+            assert!(mode.is_transparent());
+        }
+        assert!(!BlendMode::Opaque.is_transparent());
+    }
+}
@@ -0,0 +1,56 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+
+/// Esfera envolvente en espacio de mundo
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        BoundingSphere { center, radius }
+    }
+
+    /// Esfera centrada en el promedio de los puntos que los contiene a todos
+    pub fn from_points(points: impl Iterator<Item = Vec3> + Clone) -> Self {
+        let (sum, count) = points.clone().fold((Vec3::zeros(), 0usize), |(sum, count), p| (sum + p, count + 1));
+        if count == 0 {
+            return BoundingSphere::new(Vec3::zeros(), 0.0);
+        }
+
+        let center = sum / count as f32;
+        let radius = points.map(|p| (p - center).magnitude()).fold(0.0, f32::max);
+        BoundingSphere::new(center, radius)
+    }
+}
+
+/// Los seis planos del volumen de vista en espacio de mundo, extraídos de la
+/// matriz vista-proyección (método de Gribb/Hartmann). Cada plano (a, b, c, d)
+/// está normalizado y su normal apunta hacia adentro.
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    pub fn from_matrix(view_projection: &Mat4) -> Self {
+        let row = |i: usize| view_projection.row(i).transpose();
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+        // -w <= x, y, z <= w
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2].map(|plane| {
+            let length = plane.xyz().magnitude();
+            if length > 0.0 { plane / length } else { plane }
+        });
+
+        Frustum { planes }
+    }
+
+    /// Falso solo si la esfera queda entera del lado de afuera de algún plano.
+    /// Es conservador: cerca de las esquinas puede aceptar esferas invisibles.
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.xyz().dot(&sphere.center) + plane.w >= -sphere.radius)
+    }
+}
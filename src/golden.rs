@@ -0,0 +1,386 @@
+use crate::antialiasing::{AntiAliaser, AntiAliasing};
+use crate::bake::load_or_bake;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::depth::DepthMode;
+use crate::effects::Bloom;
+use crate::framebuffer::{BlendMode, Framebuffer};
+use crate::headless::SIMULATION_STEP;
+use crate::image_io::write_png;
+use crate::lighting::PointLight;
+use crate::options::Options;
+use crate::render::{DrawState, Renderer};
+use crate::scene::{Scene, RING_OUTER_RADIUS};
+use crate::shaders::{create_model_matrix, create_viewport_matrix, Uniforms};
+use crate::shadows::ShadowCasters;
+use crate::tonemap::ToneMapper;
+
+use nalgebra_glm::Vec3;
+use std::env;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// Pruebas de regresión del pipeline: cada una renderiza una escena fija en un
+// tiempo de simulación fijo y la compara con su imagen de referencia en
+// `tests/golden`. Para regenerarlas después de un cambio visual intencional:
+//
+//     GOLDEN_UPDATE=1 cargo test golden
+//
+// Si una falla, la imagen obtenida y la de diferencias quedan en `target/golden`.
+
+const REFERENCE_DIR: &str = "tests/golden";
+const OUTPUT_DIR: &str = "target/golden";
+
+/// Resolución de todas las pruebas, chica para que corran rápido
+const WIDTH: usize = 160;
+const HEIGHT: usize = 120;
+
+/// Tiempo de simulación (y de animación de los shaders) de cada render
+const TIME: f32 = 2.5;
+
+/// Un píxel cuenta como distinto si algún canal se aleja más que esto
+const PIXEL_TOLERANCE: u8 = 2;
+
+/// Fracción de píxeles que pueden pasar la tolerancia: bordes que cambian
+/// por redondeos de punto flotante en otra plataforma o compilador
+const MAX_DIFFERING_PIXELS: f64 = 0.002;
+
+/// PSNR mínimo de toda la imagen, en dB
+const MIN_PSNR: f64 = 40.0;
+
+/// Hilos del rasterizador; el resultado no depende de cuántos sean
+const THREADS: usize = 4;
+
+#[test]
+fn sun() {
+    check_golden("sun", &render_shader("sun"));
+}
+
+#[test]
+fn rocky_mars() {
+    check_golden("rocky_mars", &render_shader("rocky_mars"));
+}
+
+#[test]
+fn rocky_earth() {
+    check_golden("rocky_earth", &render_shader("rocky_earth"));
+}
+
+#[test]
+fn gas_jupiter() {
+    check_golden("gas_jupiter", &render_shader("gas_jupiter"));
+}
+
+#[test]
+fn gas_saturn() {
+    check_golden("gas_saturn", &render_shader("gas_saturn"));
+}
+
+#[test]
+fn ice_neptune() {
+    check_golden("ice_neptune", &render_shader("ice_neptune"));
+}
+
+#[test]
+fn moon() {
+    check_golden("moon", &render_shader("moon"));
+}
+
+#[test]
+fn ring() {
+    check_golden("ring", &render_shader("ring"));
+}
+
+#[test]
+fn spaceship() {
+    check_golden("spaceship", &render_shader("spaceship"));
+}
+
+#[test]
+fn test_shader() {
+    check_golden("test", &render_shader("test"));
+}
+
+/// Un nombre que ningún shader reconoce cae en `default_shader`, que ilumina
+/// el color de los vértices
+#[test]
+fn default_shader() {
+    check_golden("default", &render_shader("default"));
+}
+
+/// Las capas estáticas leídas de un mapa horneado en vez de calculadas
+#[test]
+fn rocky_earth_baked() {
+    check_golden("rocky_earth_baked", &render_shader("rocky_earth_baked"));
+}
+
+/// El sistema completo con las opciones por defecto: estrellas, órbitas,
+/// bloom, tone mapping y la nave delante de la cámara
+#[test]
+fn scene_overview() {
+    let options = Options::default();
+    check_golden("scene_overview", &render_scene(&options, options.eye, options.target));
+}
+
+#[test]
+fn scene_overview_msaa() {
+    let options = Options { antialiasing: AntiAliasing::Msaa(4), ..Options::default() };
+    check_golden("scene_overview_msaa", &render_scene(&options, options.eye, options.target));
+}
+
+/// Saturno de cerca y de costado, con los anillos translúcidos delante y
+/// detrás del planeta
+#[test]
+fn scene_saturn() {
+    let options = Options { show_spaceship: false, ..Options::default() };
+    let scene = simulated_scene(&options);
+    let saturn = scene.solar_system.planets.iter().find(|planet| planet.name == "Saturno").unwrap();
+    let position = saturn.position;
+    let up = Vec3::new(0.0, 1.0, 0.0);
+    let sideways = position.normalize().cross(&up);
+    let eye = position + (sideways * 3.0 + up * 1.2 - position.normalize()) * saturn.scale;
+    check_golden("scene_saturn", &render_scene(&options, eye, position));
+}
+
+/// La escena de prueba de z-fighting desde la cámara por defecto, con un near
+/// un millón de veces menor que la distancia a la luna. Con z invertido tiene
+/// que verse idéntica a un render con z estándar y un near holgado; con z
+/// estándar la luna y el planeta se mezclan donde se superponen. Son pocos
+/// píxeles, así que no se usa la tolerancia de las referencias.
+#[test]
+fn depth_test_scene_has_no_z_fighting_with_reversed_z() {
+    let render = |depth_mode, near| {
+        let options = Options {
+            depth_test_scene: true,
+            show_spaceship: false,
+            depth_mode,
+            near,
+            far: 1000.0,
+            ..Options::default()
+        };
+        render_scene(&options, options.eye, options.target)
+    };
+    let reference = render(DepthMode::Standard, 1.0);
+
+    let reversed = compare(&render(DepthMode::ReversedZ, EXTREME_NEAR).buffer, &reference.buffer);
+    assert_eq!(reversed.differing_pixels, 0, "z invertido con near {}", EXTREME_NEAR);
+
+    // Sin esto la prueba pasaría aunque la escena no detectara nada
+    let standard = compare(&render(DepthMode::Standard, EXTREME_NEAR).buffer, &reference.buffer);
+    assert!(standard.differing_pixels > 0, "z estándar debería mostrar z-fighting con near {}", EXTREME_NEAR);
+}
+
+/// Near de la prueba de z-fighting del README: la luna queda a unas 33 unidades
+const EXTREME_NEAR: f32 = 0.00003;
+
+/// La escena por defecto (o la de z-fighting) avanzada con pasos fijos hasta
+/// `TIME`, como en headless
+fn simulated_scene(options: &Options) -> Scene {
+    let mut scene = if options.depth_test_scene { Scene::depth_test() } else { Scene::new() }.unwrap();
+    scene.show_spaceship = options.show_spaceship;
+    for _ in 0..(TIME / SIMULATION_STEP).round() as usize {
+        scene.update(SIMULATION_STEP);
+    }
+    scene
+}
+
+/// Renderiza la escena con el mismo pipeline que headless
+fn render_scene(options: &Options, eye: Vec3, target: Vec3) -> Framebuffer {
+    let scene = simulated_scene(options);
+    let camera = Camera::new(eye, target, Vec3::new(0.0, 1.0, 0.0)).with_clip_planes(options.near, options.far);
+
+    let mut renderer = Renderer::new(THREADS);
+    renderer.depth_mode = options.depth_mode;
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.set_background_color(Color::new(5, 5, 20));
+    AntiAliaser::new(options.antialiasing).render(&scene, &mut renderer, &mut framebuffer, &camera, TIME);
+
+    let mut bloom = Bloom::new(options.bloom_threshold, options.bloom_intensity);
+    bloom.enabled = options.bloom;
+    bloom.apply(&mut framebuffer);
+    framebuffer.resolve(&ToneMapper::new(options.tone_mapping, options.exposure));
+    framebuffer
+}
+
+/// Un solo objeto con `shader_type` en el origen, iluminado desde un costado
+/// y sin post-proceso salvo el tone mapping. `rocky_earth_baked` usa el
+/// shader de la Tierra con sus capas estáticas horneadas a 256×128.
+fn render_shader(shader_type: &str) -> Framebuffer {
+    let scene = Scene::new().unwrap();
+    let (shader_type, baked_layers) = match shader_type {
+        "rocky_earth_baked" => {
+            let (texture, _) = load_or_bake("rocky_earth", 256, None, THREADS).unwrap().unwrap();
+            ("rocky_earth", Some(Arc::new(texture)))
+        }
+        other => (other, None),
+    };
+
+    // Cada malla escalada a radio 1; los anillos inclinados para verlos de frente
+    let ring_indices: Vec<u32> = (0..scene.ring_vertices.len() as u32).collect();
+    let (vertices, indices, radius, rotation, state) = match shader_type {
+        "ring" => (
+            &scene.ring_vertices[..],
+            &ring_indices[..],
+            RING_OUTER_RADIUS,
+            Vec3::new(0.9, 0.0, 0.0),
+            DrawState::double_sided().with_blend_mode(BlendMode::Alpha),
+        ),
+        "spaceship" => (
+            &scene.spaceship.model.vertices[..],
+            &scene.spaceship.model.indices[..],
+            scene.spaceship_radius,
+            Vec3::new(0.3, 0.8, 0.0),
+            DrawState::default(),
+        ),
+        _ => (
+            &scene.sphere_model.vertices[..],
+            &scene.sphere_model.indices[..],
+            scene.sphere_radius,
+            Vec3::new(0.0, 0.6, 0.0),
+            DrawState::default(),
+        ),
+    };
+
+    let camera = Camera::new(Vec3::new(0.0, 0.8, 3.2), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+    let (width, height) = (WIDTH as f32, HEIGHT as f32);
+    let uniforms = Uniforms {
+        model_matrix: create_model_matrix(Vec3::zeros(), 1.0 / radius, rotation),
+        view_matrix: camera.get_view_matrix(),
+        projection_matrix: camera.get_projection_matrix(width / height),
+        viewport_matrix: create_viewport_matrix(width, height),
+        time: TIME,
+        camera_position: camera.eye,
+        lights: Arc::new(vec![PointLight::new(Vec3::new(-5.0, 2.0, 1.0), Color::new(255, 246, 232), 1.0)]),
+        shadows: Arc::new(ShadowCasters::new()),
+        surface_map: None,
+        baked_layers,
+    };
+
+    let mut renderer = Renderer::new(THREADS);
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.set_background_color(Color::new(5, 5, 20));
+    framebuffer.clear();
+    renderer.draw_indexed(&uniforms, vertices, indices, shader_type, &state);
+    renderer.flush(&mut framebuffer);
+    framebuffer.resolve(&ToneMapper::new(Options::default().tone_mapping, 1.0));
+    framebuffer
+}
+
+/// Resultado de comparar una imagen con su referencia
+struct Comparison {
+    differing_pixels: usize,
+    max_difference: u8,
+    psnr: f64,
+    /// Referencia oscurecida, con los píxeles fuera de tolerancia en rojo y
+    /// el resto de las diferencias amplificadas en gris
+    diff: Vec<u32>,
+}
+
+impl Comparison {
+    fn passes(&self) -> bool {
+        self.differing_pixels as f64 <= MAX_DIFFERING_PIXELS * (WIDTH * HEIGHT) as f64 && self.psnr >= MIN_PSNR
+    }
+}
+
+fn compare(actual: &[u32], reference: &[u32]) -> Comparison {
+    let channels = |pixel: u32| [(pixel >> 16) & 0xFF, (pixel >> 8) & 0xFF, pixel & 0xFF];
+    let mut differing_pixels = 0;
+    let mut max_difference = 0;
+    let mut squared_error = 0.0;
+    let mut diff = Vec::with_capacity(reference.len());
+
+    for (&actual, &reference) in actual.iter().zip(reference) {
+        let (a, b) = (channels(actual), channels(reference));
+        let difference = (0..3).map(|i| a[i].abs_diff(b[i])).max().unwrap() as u8;
+        squared_error += (0..3).map(|i| (a[i] as f64 - b[i] as f64).powi(2)).sum::<f64>();
+        max_difference = max_difference.max(difference);
+
+        diff.push(if difference > PIXEL_TOLERANCE {
+            differing_pixels += 1;
+            0xFF0000
+        } else if difference > 0 {
+            0x010101 * (difference as u32 * 64).min(255)
+        } else {
+            (reference >> 2) & 0x3F3F3F
+        });
+    }
+
+    let mean_squared_error = squared_error / (reference.len() * 3) as f64;
+    let psnr = if mean_squared_error == 0.0 {
+        f64::INFINITY
+    } else {
+        10.0 * (255.0 * 255.0 / mean_squared_error).log10()
+    };
+    Comparison { differing_pixels, max_difference, psnr, diff }
+}
+
+/// Compara el framebuffer con `tests/golden/<name>.png`, o lo guarda como
+/// referencia nueva con `GOLDEN_UPDATE=1`
+fn check_golden(name: &str, framebuffer: &Framebuffer) {
+    let reference_path = Path::new(REFERENCE_DIR).join(format!("{}.png", name));
+    if env::var_os("GOLDEN_UPDATE").is_some() {
+        fs::create_dir_all(REFERENCE_DIR).unwrap();
+        write_png(&reference_path, &framebuffer.buffer, framebuffer.width, framebuffer.height).unwrap();
+        return;
+    }
+
+    let actual_path = output_path(name, "actual");
+    let reference = read_png(&reference_path).unwrap_or_else(|e| {
+        write_png(&actual_path, &framebuffer.buffer, framebuffer.width, framebuffer.height).unwrap();
+        panic!("{}: {} (se genera con GOLDEN_UPDATE=1; render actual en {})", name, e, actual_path.display())
+    });
+    if (reference.0, reference.1) != (framebuffer.width, framebuffer.height) {
+        panic!("{}: la referencia es de {}x{} y el render de {}x{}", name, reference.0, reference.1, framebuffer.width, framebuffer.height);
+    }
+
+    let comparison = compare(&framebuffer.buffer, &reference.2);
+    let diff_path = output_path(name, "diff");
+    if comparison.passes() {
+        // Sin restos de una falla anterior
+        let _ = fs::remove_file(&actual_path);
+        let _ = fs::remove_file(&diff_path);
+    } else {
+        write_png(&actual_path, &framebuffer.buffer, framebuffer.width, framebuffer.height).unwrap();
+        write_png(&diff_path, &comparison.diff, framebuffer.width, framebuffer.height).unwrap();
+        panic!(
+            "{}: {} píxeles difieren en más de {} (máximo {}, se permiten {:.0}), PSNR {:.2} dB (mínimo {} dB)\n  render: {}\n  diferencias: {}",
+            name,
+            comparison.differing_pixels,
+            PIXEL_TOLERANCE,
+            comparison.max_difference,
+            MAX_DIFFERING_PIXELS * (WIDTH * HEIGHT) as f64,
+            comparison.psnr,
+            MIN_PSNR,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+fn output_path(name: &str, kind: &str) -> PathBuf {
+    fs::create_dir_all(OUTPUT_DIR).unwrap();
+    Path::new(OUTPUT_DIR).join(format!("{}_{}.png", name, kind))
+}
+
+/// Lee un PNG RGB de 8 bits como el que escribe `write_png`
+fn read_png(path: &Path) -> Result<(usize, usize, Vec<u32>), String> {
+    let file = File::open(path).map_err(|e| format!("No se pudo abrir {}: {}", path.display(), e))?;
+    let mut reader = png::Decoder::new(BufReader::new(file))
+        .read_info()
+        .map_err(|e| format!("Error leyendo {}: {}", path.display(), e))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|e| format!("Error leyendo {}: {}", path.display(), e))?;
+    if info.color_type != png::ColorType::Rgb || info.bit_depth != png::BitDepth::Eight {
+        return Err(format!("{} no es un PNG RGB de 8 bits", path.display()));
+    }
+
+    let pixels = buffer[..info.buffer_size()]
+        .chunks_exact(3)
+        .map(|p| (p[0] as u32) << 16 | (p[1] as u32) << 8 | p[2] as u32)
+        .collect();
+    Ok((info.width as usize, info.height as usize, pixels))
+}
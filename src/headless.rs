@@ -0,0 +1,99 @@
+use crate::camera::Camera;
+use crate::antialiasing::AntiAliaser;
+use crate::color::Color;
+use crate::effects::Bloom;
+use crate::framebuffer::{scaled_size, Framebuffer};
+use crate::image_io::{save_framebuffer, write_image};
+use crate::options::Options;
+use crate::render::Renderer;
+use crate::scene::Scene;
+use crate::tonemap::{ToneMapper, ToneMapping};
+
+use nalgebra_glm::Vec3;
+use std::fs;
+
+/// Paso fijo de simulación, el mismo que usa el loop de la ventana
+pub const SIMULATION_STEP: f32 = 1.0 / 60.0;
+
+/// Tiempo máximo del último frame: un día son 5 millones de pasos fijos y
+/// unos segundos de simulación antes del primer render
+pub const MAX_SIMULATION_TIME: f32 = 86_400.0;
+
+/// Renderiza la escena sin ventana y guarda cada frame como imagen
+pub fn run(options: &Options) -> Result<(), String> {
+    fs::create_dir_all(&options.output_dir)
+        .map_err(|e| format!("No se pudo crear {}: {}", options.output_dir.display(), e))?;
+
+    let mut scene = if options.depth_test_scene { Scene::depth_test()? } else { Scene::new()? };
+    scene.show_spaceship = options.show_spaceship;
+    scene.set_inverse_square(options.inverse_square);
+    scene.load_surface_maps(&options.surface_maps, options.texture_filter)?;
+    if options.bake {
+        scene.bake_static_layers(options.bake_resolution, options.bake_cache.as_deref(), options.threads)?;
+    }
+    for (name, model) in [("sphere.obj", &scene.sphere_model), ("spaceship.obj", &scene.spaceship.model)] {
+        println!("📦 {}: {} vértices únicos para {} índices", name, model.vertices.len(), model.indices.len());
+    }
+
+    let camera = Camera::new(options.eye, options.target, Vec3::new(0.0, 1.0, 0.0))
+        .with_clip_planes(options.near, options.far);
+
+    let mut renderer = Renderer::new(options.threads);
+    renderer.early_z = options.early_z;
+    renderer.depth_mode = options.depth_mode;
+    renderer.debug_view = options.debug_view;
+    renderer.render_scale = options.render_scale;
+    let (render_width, render_height) = scaled_size(options.width, options.height, options.render_scale);
+    let mut framebuffer = Framebuffer::new(render_width, render_height);
+    let mut upscaled = Vec::new();
+    framebuffer.set_background_color(Color::new(5, 5, 20));
+    let tone_mapper = ToneMapper::new(options.tone_mapping, options.exposure);
+    let mut bloom = Bloom::new(options.bloom_threshold, options.bloom_intensity);
+    bloom.enabled = options.bloom;
+    let mut antialiaser = AntiAliaser::new(options.antialiasing);
+
+    let frame_step = 1.0 / options.fps;
+    let mut simulated_steps = 0usize;
+
+    println!("🖼️  Renderizando {} frame(s) de {}x{} en {}",
+             options.frames, options.width, options.height, options.output_dir.display());
+
+    for frame in 0..options.frames {
+        let time = options.time + frame as f32 * frame_step;
+
+        // Avanzar la simulación con pasos fijos hasta el tiempo del frame
+        let target_steps = (time / SIMULATION_STEP).round().max(0.0) as usize;
+        while simulated_steps < target_steps {
+            scene.update(SIMULATION_STEP);
+            simulated_steps += 1;
+        }
+
+        antialiaser.render(&scene, &mut renderer, &mut framebuffer, &camera, time);
+        // Las vistas de depuración se guardan tal cual, sin bloom ni tone mapping
+        if renderer.debug_view.replaces_output() {
+            framebuffer.resolve(&ToneMapper::new(ToneMapping::Clamp, 1.0));
+        } else {
+            bloom.apply(&mut framebuffer);
+            framebuffer.resolve(&tone_mapper);
+        }
+
+        let path = options.output_dir.join(format!(
+            "frame_{:04}.{}",
+            frame,
+            options.format.extension()
+        ));
+        if framebuffer.width == options.width && framebuffer.height == options.height {
+            save_framebuffer(&framebuffer, &path, options.format)?;
+        } else {
+            framebuffer.upscale_into(&mut upscaled, options.width, options.height);
+            write_image(&path, &upscaled, options.width, options.height, options.format)?;
+        }
+
+        let stats = &renderer.stats;
+        println!("  ✅ {} ({} objetos dibujados, {} fuera de vista, {} vértices transformados, {} triángulos, {} descartados por cara, {} fragmentos, {} shaders, {:.0}% ahorrado)",
+                 path.display(), stats.objects_drawn, stats.objects_frustum_culled, stats.vertex_shader_invocations, stats.triangles, stats.triangles_culled, stats.fragments,
+                 stats.shader_invocations, stats.saved_percentage());
+    }
+
+    Ok(())
+}
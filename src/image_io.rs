@@ -0,0 +1,109 @@
+use crate::framebuffer::Framebuffer;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Ppm,
+}
+
+impl ImageFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "ppm" => Some(ImageFormat::Ppm),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Ppm => "ppm",
+        }
+    }
+}
+
+/// Convierte un buffer 0x00RRGGBB en bytes RGB consecutivos
+pub fn buffer_to_rgb(buffer: &[u32]) -> Vec<u8> {
+    let mut rgb = Vec::with_capacity(buffer.len() * 3);
+    for pixel in buffer {
+        rgb.push(((pixel >> 16) & 0xFF) as u8);
+        rgb.push(((pixel >> 8) & 0xFF) as u8);
+        rgb.push((pixel & 0xFF) as u8);
+    }
+    rgb
+}
+
+/// Guarda un buffer como PPM binario (P6), sin dependencias externas
+pub fn write_ppm(path: &Path, buffer: &[u32], width: usize, height: usize) -> Result<(), String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Error creando {}: {}", path.display(), e))?;
+    let mut writer = BufWriter::new(file);
+
+    // El flush explícito hace que un error al vaciar el buffer no se pierda en el drop
+    write!(writer, "P6\n{} {}\n255\n", width, height)
+        .and_then(|_| writer.write_all(&buffer_to_rgb(buffer)))
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Error escribiendo {}: {}", path.display(), e))
+}
+
+/// Guarda un buffer como PNG RGB de 8 bits
+pub fn write_png(path: &Path, buffer: &[u32], width: usize, height: usize) -> Result<(), String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Error creando {}: {}", path.display(), e))?;
+    let writer = BufWriter::new(file);
+
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut png_writer| {
+            png_writer.write_image_data(&buffer_to_rgb(buffer))?;
+            // Escribe el IEND y vacía el `BufWriter`, devolviendo sus errores
+            png_writer.finish()
+        })
+        .map_err(|e| format!("Error escribiendo {}: {}", path.display(), e))
+}
+
+/// Guarda bytes RGBA consecutivos como PNG RGBA de 8 bits
+pub fn write_png_rgba(path: &Path, rgba: &[u8], width: usize, height: usize) -> Result<(), String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Error creando {}: {}", path.display(), e))?;
+    let writer = BufWriter::new(file);
+
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut png_writer| {
+            png_writer.write_image_data(rgba)?;
+            // Escribe el IEND y vacía el `BufWriter`, devolviendo sus errores
+            png_writer.finish()
+        })
+        .map_err(|e| format!("Error escribiendo {}: {}", path.display(), e))
+}
+
+pub fn write_image(
+    path: &Path,
+    buffer: &[u32],
+    width: usize,
+    height: usize,
+    format: ImageFormat,
+) -> Result<(), String> {
+    match format {
+        ImageFormat::Png => write_png(path, buffer, width, height),
+        ImageFormat::Ppm => write_ppm(path, buffer, width, height),
+    }
+}
+
+/// Guarda el contenido actual del framebuffer
+pub fn save_framebuffer(framebuffer: &Framebuffer, path: &Path, format: ImageFormat) -> Result<(), String> {
+    write_image(path, &framebuffer.buffer, framebuffer.width, framebuffer.height, format)
+}
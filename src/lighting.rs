@@ -0,0 +1,121 @@
+use nalgebra_glm::Vec3;
+use crate::color::{Color, HdrColor};
+use crate::shadows::ShadowCasters;
+
+/// Cómo se atenúa una luz con la distancia
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Falloff {
+    /// Misma intensidad a cualquier distancia
+    None,
+    /// Ley del cuadrado inverso, normalizada para valer 1 a `reference_distance`
+    InverseSquare { reference_distance: f32 },
+}
+
+impl Falloff {
+    pub fn attenuation(&self, distance: f32) -> f32 {
+        match *self {
+            Falloff::None => 1.0,
+            Falloff::InverseSquare { reference_distance } => {
+                let ratio = reference_distance / distance.max(1e-3);
+                ratio * ratio
+            }
+        }
+    }
+}
+
+/// Luz puntual en espacio de mundo. `radius` es el tamaño de la fuente, que
+/// da la penumbra de las sombras.
+#[derive(Clone, Copy, Debug)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub color: Color,
+    pub intensity: f32,
+    pub radius: f32,
+    pub falloff: Falloff,
+}
+
+impl PointLight {
+    pub fn new(position: Vec3, color: Color, intensity: f32) -> Self {
+        PointLight {
+            position,
+            color,
+            intensity,
+            radius: 0.0,
+            falloff: Falloff::None,
+        }
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+}
+
+/// Luz directa que llega a un punto de una superficie
+#[derive(Clone, Copy, Debug)]
+pub struct LightSample {
+    /// Dirección hacia la luz que más aporta (para especulares y efectos)
+    pub direction: Vec3,
+    /// Suma de n·l × intensidad × atenuación × visibilidad de todas las luces
+    pub diffuse: f32,
+    /// Color lineal de la luz recibida, promediado según el aporte de cada una
+    pub color: HdrColor,
+}
+
+/// Evalúa todas las luces en `point` con normal `normal`. Cada luz se
+/// atenúa según su distancia y las sombras de `shadows`.
+pub fn illuminate(lights: &[PointLight], shadows: &ShadowCasters, point: &Vec3, normal: &Vec3) -> LightSample {
+    let mut diffuse = 0.0;
+    let mut weighted = HdrColor::black();
+    let mut strongest = (f32::NEG_INFINITY, Vec3::new(0.0, 1.0, 0.0));
+
+    for light in lights {
+        let to_light = light.position - point;
+        let distance = to_light.magnitude();
+        if distance <= 1e-6 {
+            continue;
+        }
+        let direction = to_light / distance;
+
+        let lambert = normal.dot(&direction).max(0.0);
+        let strength = light.intensity * light.falloff.attenuation(distance);
+        let contribution = if lambert > 0.0 {
+            lambert * strength * shadows.visibility(point, &light.position, light.radius)
+        } else {
+            0.0
+        };
+
+        // La dirección principal no depende de n·l: el lado nocturno también la necesita
+        if strength > strongest.0 {
+            strongest = (strength, direction);
+        }
+
+        diffuse += contribution;
+        weighted = weighted + HdrColor::from(light.color) * contribution;
+    }
+
+    let color = if diffuse > 0.0 {
+        weighted * (1.0 / diffuse)
+    } else {
+        HdrColor::new(1.0, 1.0, 1.0)
+    };
+
+    LightSample { direction: strongest.1, diffuse, color }
+}
+
+/// Combina luz ambiente (sin color) y luz directa (teñida por la luz) en
+/// espacio lineal: `albedo × ambient + albedo × luz × direct`. No se recorta:
+/// lo que pase de 1.0 lo comprime el tone mapping al final del frame.
+pub fn apply_lighting(albedo: HdrColor, ambient: f32, direct: f32, light_color: HdrColor) -> HdrColor {
+    HdrColor {
+        r: albedo.r * (ambient + light_color.r * direct),
+        g: albedo.g * (ambient + light_color.g * direct),
+        b: albedo.b * (ambient + light_color.b * direct),
+        a: albedo.a,
+    }
+}
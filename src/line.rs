@@ -0,0 +1,106 @@
+use crate::color::HdrColor;
+use crate::vertex::Vertex;
+
+/// Segmento ya recortado y proyectado a pantalla, con ancho constante en píxeles
+pub struct Line {
+    pub v1: Vertex,
+    pub v2: Vertex,
+    pub width: f32,
+}
+
+/// Píxel tocado por una línea: cobertura analítica en [0, 1], profundidad y color
+#[derive(Clone, Copy, Debug)]
+pub struct LineSample {
+    pub x: i32,
+    pub y: i32,
+    pub depth: f32,
+    pub coverage: f32,
+    pub color: HdrColor,
+}
+
+impl Line {
+    pub fn new(v1: Vertex, v2: Vertex, width: f32) -> Self {
+        Line { v1, v2, width }
+    }
+
+    /// Medio ancho usado para la cobertura. Las líneas de menos de un píxel
+    /// se dibujan de un píxel y se compensan bajando la cobertura.
+    fn half_width(&self) -> f32 {
+        self.width.max(1.0) * 0.5
+    }
+
+    /// Distancia media a la cámara, para ordenar junto con los transparentes
+    pub fn view_depth(&self) -> f32 {
+        (self.v1.clip_position.w + self.v2.clip_position.w) * 0.5
+    }
+
+    /// Caja envolvente en píxeles, ensanchada por el grosor de la línea
+    pub fn bounding_box(&self) -> (i32, i32, i32, i32) {
+        let p1 = self.v1.transformed_position;
+        let p2 = self.v2.transformed_position;
+        let pad = self.half_width() + 1.0;
+
+        (
+            (p1.x.min(p2.x) - pad).floor() as i32,
+            (p1.y.min(p2.y) - pad).floor() as i32,
+            (p1.x.max(p2.x) + pad).floor() as i32,
+            (p1.y.max(p2.y) + pad).floor() as i32,
+        )
+    }
+
+    /// Recorre los píxeles dentro de `rect` con cobertura distinta de cero.
+    /// La cobertura es el área aproximada del píxel bajo la banda de la línea
+    /// (distancia del centro del píxel al segmento); los extremos son rectos y
+    /// semiabiertos para que los segmentos consecutivos no se solapen.
+    pub fn rasterize<F: FnMut(LineSample)>(&self, rect: (i32, i32, i32, i32), mut emit: F) {
+        let p1 = self.v1.transformed_position;
+        let p2 = self.v2.transformed_position;
+        let dx = p2.x - p1.x;
+        let dy = p2.y - p1.y;
+        let length_sq = dx * dx + dy * dy;
+        if length_sq < 1e-8 {
+            return;
+        }
+        let length = length_sq.sqrt();
+
+        let half_width = self.half_width();
+        let thinness = self.width.min(1.0);
+        let c1 = HdrColor::from(self.v1.color);
+        let c2 = HdrColor::from(self.v2.color);
+        let inv_w1 = 1.0 / self.v1.clip_position.w;
+        let inv_w2 = 1.0 / self.v2.clip_position.w;
+
+        let (box_min_x, box_min_y, box_max_x, box_max_y) = self.bounding_box();
+        let min_x = box_min_x.max(rect.0);
+        let min_y = box_min_y.max(rect.1);
+        let max_x = box_max_x.min(rect.2);
+        let max_y = box_max_y.min(rect.3);
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let px = x as f32 + 0.5 - p1.x;
+                let py = y as f32 + 0.5 - p1.y;
+
+                // Parámetro a lo largo del segmento y distancia perpendicular
+                let t = (px * dx + py * dy) / length_sq;
+                if !(0.0..1.0).contains(&t) {
+                    continue;
+                }
+                let distance = (px * dy - py * dx).abs() / length;
+
+                let coverage = (half_width + 0.5 - distance).clamp(0.0, 1.0) * thinness;
+                if coverage <= 0.0 {
+                    continue;
+                }
+
+                // Profundidad afín en pantalla; color con corrección de perspectiva
+                let depth = p1.z + (p2.z - p1.z) * t;
+                let a = (1.0 - t) * inv_w1;
+                let b = t * inv_w2;
+                let color = c1.lerp(&c2, b / (a + b));
+
+                emit(LineSample { x, y, depth, coverage, color });
+            }
+        }
+    }
+}
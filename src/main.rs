@@ -1,8 +1,11 @@
 mod color;
 mod framebuffer;
 mod triangle;
+mod line;
+mod point;
 mod obj_loader;
 mod vertex;
+mod varyings;
 mod fragment;
 mod shaders;
 mod camera;
@@ -11,71 +14,78 @@ mod celestial;
 mod effects;
 mod spacecraft;
 mod utils;
+mod render;
+mod tile;
+mod clipping;
+mod frustum;
+mod shadows;
+mod lighting;
+mod scene;
+mod image_io;
+mod options;
+mod headless;
+mod tonemap;
+mod antialiasing;
+mod depth;
+mod debug_view;
+mod texture;
+mod bake;
+mod screenshot;
+mod recording;
+#[cfg(test)]
+mod golden;
 
-use crate::spacecraft::Spaceship;
 use crate::color::Color;
-use crate::framebuffer::{Framebuffer, SCREEN_WIDTH, SCREEN_HEIGHT};
-use crate::triangle::Triangle;
-use crate::obj_loader::Model;
-use crate::vertex::Vertex;
-use crate::shaders::{vertex_shader, fragment_shader, create_model_matrix, create_viewport_matrix, Uniforms};
+use crate::framebuffer::{scaled_size, Framebuffer};
 use crate::camera::Camera;
-use crate::ring::create_ring_vertices;
-use crate::celestial::{SolarSystem, CelestialBody};
-use crate::effects::{create_orbit_lines, WarpEffect};
-use crate::utils::{check_sphere_collision, resolve_sphere_collision, SafeZone};
+use crate::celestial::CelestialBody;
+use crate::effects::{Bloom, WarpEffect};
+use crate::utils::{check_sphere_collision, resolve_sphere_collision};
+use crate::options::{Options, USAGE};
+use crate::scene::Scene;
+use crate::render::Renderer;
+use crate::tonemap::{ToneMapper, ToneMapping};
+use crate::debug_view::DebugView;
+use crate::antialiasing::AntiAliaser;
+use crate::lighting::Falloff;
+use crate::screenshot::Screenshots;
+use crate::recording::Recorder;
+use crate::headless::SIMULATION_STEP;
 
 use minifb::{Key, Window, WindowOptions};
 use nalgebra_glm::Vec3;
 use std::time::Instant;
 
-fn render(
-    framebuffer: &mut Framebuffer,
-    uniforms: &Uniforms,
-    vertex_array: &[Vertex],
-    shader_type: &str,
-) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
+/// Radio de colisión de la cámara; se agranda si el near plane es mayor
+const CAMERA_RADIUS: f32 = 0.15;
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push(Triangle::new_from_vertices(
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ));
-        }
-    }
+fn main() {
+    let options = Options::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
 
-    let mut all_fragments = Vec::new();
-    for triangle in &triangles {
-        let fragments = triangle.draw(framebuffer);
-        all_fragments.extend(fragments);
+    if options.show_help {
+        println!("{}", USAGE);
+        return;
     }
 
-    for fragment in all_fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-        
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = fragment_shader(&fragment, uniforms, shader_type);
-            framebuffer.set_current_color(shaded_color);
-            framebuffer.point(x, y, fragment.depth);
+    if options.headless {
+        if let Err(e) = headless::run(&options) {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
         }
+        return;
     }
-}
 
-fn main() {
     let mut window = Window::new(
         "Sistema Solar - Proyecto 3",
-        SCREEN_WIDTH,
-        SCREEN_HEIGHT,
-        WindowOptions::default(),
+        options.width,
+        options.height,
+        WindowOptions {
+            resize: true,
+            ..WindowOptions::default()
+        },
     )
     .unwrap_or_else(|e| {
         panic!("No se pudo crear la ventana: {}", e);
@@ -83,41 +93,48 @@ fn main() {
 
     window.set_target_fps(60);
 
-    let mut framebuffer = Framebuffer::new(SCREEN_WIDTH, SCREEN_HEIGHT);
+    let mut renderer = Renderer::new(options.threads);
+    renderer.early_z = options.early_z;
+    renderer.depth_mode = options.depth_mode;
+    renderer.debug_view = options.debug_view;
+    renderer.render_scale = options.render_scale;
+
+    // Con escala de render < 1 se dibuja más chico y se escala a la ventana
+    let (mut window_width, mut window_height) = (options.width, options.height);
+    let (render_width, render_height) = scaled_size(window_width, window_height, options.render_scale);
+    let mut framebuffer = Framebuffer::new(render_width, render_height);
+    let mut display_buffer = Vec::new();
     framebuffer.set_background_color(Color::new(5, 5, 20));
-
-    // Cargar modelo de esfera para planetas
-    let mut sphere_model = Model::load_from_file("assets/models/sphere.obj")
-        .expect("No se pudo cargar sphere.obj");
-    sphere_model.normalize_and_center(1.0);
-
-    // CARGAR MODELO DE NAVE ESPACIAL
-    let spaceship_model = Model::load_from_file("assets/models/spaceship.obj")
-        .expect("No se pudo cargar spaceship.obj");
-    let mut spaceship = Spaceship::new(spaceship_model);
-    spaceship.scale = 0.05;
-    println!("✅ Nave espacial cargada con {} vértices", spaceship.model.vertices.len());
-
-    // Crear anillos de Saturno
-    let ring_vertices = create_ring_vertices(1.2, 1.8, 100);
-
-    // CREAR EL SISTEMA SOLAR
-    let mut solar_system = SolarSystem::new();
-
-    // CREAR GEOMETRÍA DE ÓRBITAS
-    let orbit_lines: Vec<Vec<Vertex>> = solar_system.planets
-        .iter()
-        .map(|planet| create_orbit_lines(planet.orbit.radius, 80, planet.orbit.inclination))
-        .collect();
+    let mut tone_mapper = ToneMapper::new(options.tone_mapping, options.exposure);
+    let mut bloom = Bloom::new(options.bloom_threshold, options.bloom_intensity);
+    bloom.enabled = options.bloom;
+    let mut antialiaser = AntiAliaser::new(options.antialiasing);
+    let screenshots = Screenshots::from_options(&options);
+
+    let scene = if options.depth_test_scene { Scene::depth_test() } else { Scene::new() };
+    let mut scene = scene.unwrap_or_else(|e| panic!("{}", e));
+    scene.show_spaceship = options.show_spaceship;
+    scene.set_inverse_square(options.inverse_square);
+    scene.load_surface_maps(&options.surface_maps, options.texture_filter)
+        .unwrap_or_else(|e| panic!("{}", e));
+    if options.bake {
+        scene.bake_static_layers(options.bake_resolution, options.bake_cache.as_deref(), renderer.threads)
+            .unwrap_or_else(|e| panic!("{}", e));
+    }
+    println!("✅ Nave espacial cargada: {} triángulos, {} vértices únicos",
+             scene.spaceship.model.triangle_count(), scene.spaceship.model.vertices.len());
+    println!("✅ Esfera cargada: {} triángulos, {} vértices únicos",
+             scene.sphere_model.triangle_count(), scene.sphere_model.vertices.len());
 
     println!("🌌 Sistema Solar - Proyecto 3");
     println!("================================");
-    println!("✅ Planetas en el sistema: {}", solar_system.planet_count());
-    for (i, planet) in solar_system.planets.iter().enumerate() {
+    println!("✅ Planetas en el sistema: {}", scene.solar_system.planet_count());
+    for (i, planet) in scene.solar_system.planets.iter().enumerate() {
         println!("  [{}] {} - Radio orbital: {:.1}", 
                  i + 1, planet.name, planet.orbit.radius);
     }
-    println!("✅ Órbitas renderizables: {}", orbit_lines.len());
+    println!("✅ Tramos de órbita renderizables: {}", scene.orbit_batches.len());
+    println!("✅ Hilos de rasterización: {}", renderer.threads);
     
     println!("\n🎮 CONTROLES:");
     println!("  [1-8] Seleccionar planeta");
@@ -132,6 +149,18 @@ fn main() {
     println!("     ←/→: Mirar izquierda/derecha");
     println!("  Q/E: Zoom in/out");
     println!("  R: Resetear cámara (volver al Sol)");
+    println!("  Z: Activar/desactivar early-z (contadores en el título)");
+    println!("  T: Cambiar tone mapping (ninguno/Reinhard/ACES)");
+    println!("  +/-: Subir/bajar exposición");
+    println!("  B: Activar/desactivar bloom");
+    println!("  M: Cambiar antialiasing (ninguno/MSAA 4x/SSAA 4x)");
+    println!("  L: Activar/desactivar la atenuación de la luz por cuadrado inverso");
+    println!("  P: Cambiar profundidad (estándar/invertida)");
+    println!("  H: Usar/no usar los mapas horneados de los shaders");
+    println!("  F12: Guardar captura en {}", screenshots.dir.display());
+    println!("  F9: Empezar/terminar grabación en {} ({} a {} fps)",
+             options.record_dir.display(), options.record_format.name(), options.record_fps);
+    println!("  F1-F5: Vistas de depuración (alambre/normales/profundidad/UVs/overdraw)");
     println!("  ESC: Salir");
     println!("================================\n");
 
@@ -139,31 +168,60 @@ fn main() {
         Vec3::new(0.0, 15.0, 30.0),
         Vec3::new(0.0, 0.0, 0.0),
         Vec3::new(0.0, 1.0, 0.0),
-    );
+    ).with_clip_planes(options.near, options.far);
 
-    // CREAR SISTEMA DE WARP
-    let mut warp_effect = WarpEffect::new();
-
-    // 🆕 CREAR SISTEMA DE SAFE ZONE
-    let mut safe_zone = SafeZone::new(camera.eye);    
+    // La cámara no debe acercarse a una superficie más que el near plane
+    let camera_radius = CAMERA_RADIUS.max(options.near * 1.5);
 
     // CREAR SISTEMA DE WARP
     let mut warp_effect = WarpEffect::new();
 
     let start_time = Instant::now();
+    let mut title_timer = Instant::now();
+    let mut frames_since_title = 0;
     let mut _selected_planet: Option<usize> = None;
 
+    // Grabación en curso; al terminar, el reloj sigue desde el último frame grabado
+    let mut recorder: Option<Recorder> = None;
+    let mut start_recording = options.record;
+    let mut time_offset = 0.0;
+
     println!("🎬 Iniciando simulación...\n");
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
-        let time = start_time.elapsed().as_secs_f32();
-        let delta_time = 1.0 / 60.0;
+        // GRABACIÓN (F9 o --record)
+        if window.is_key_pressed(Key::F9, minifb::KeyRepeat::No) || start_recording {
+            start_recording = false;
+            match recorder.take() {
+                Some(recording) => time_offset = finish_recording(recording) - start_time.elapsed().as_secs_f32(),
+                None => {
+                    let time = start_time.elapsed().as_secs_f32() + time_offset;
+                    match Recorder::start(&options, window_width, window_height, time) {
+                        Ok(recording) => {
+                            println!("\n🔴 Grabando {}x{} a {} fps en {}",
+                                     recording.width, recording.height, recording.fps, recording.path.display());
+                            recorder = Some(recording);
+                        }
+                        Err(e) => eprintln!("\n❌ {}", e),
+                    }
+                }
+            }
+        }
 
-        // ACTUALIZAR SISTEMA SOLAR
-        solar_system.update(delta_time);
+        // Grabando, cada frame avanza 1/fps de simulación aunque tarde más
+        // en dibujarse; si no, el tiempo es el del reloj
+        let (time, delta_time, simulation_steps) = match recorder.as_mut() {
+            Some(recording) => {
+                let (time, steps) = recording.advance();
+                (time, 1.0 / recording.fps, steps)
+            }
+            None => (start_time.elapsed().as_secs_f32() + time_offset, SIMULATION_STEP, 1),
+        };
 
-        // ACTUALIZAR NAVE
-        spaceship.update(delta_time);
+        // ACTUALIZAR SISTEMA SOLAR Y NAVE
+        for _ in 0..simulation_steps {
+            scene.update(SIMULATION_STEP);
+        }
 
         // ACTUALIZAR EFECTO WARP
         if let Some(new_position) = warp_effect.update(delta_time) {
@@ -173,7 +231,7 @@ fn main() {
         // SI HAY UN PLANETA SELECCIONADO, SEGUIRLO SUAVEMENTE (solo si no hay warp activo)
         if !warp_effect.is_active() {
             if let Some(index) = _selected_planet {
-                if let Some(planet) = solar_system.get_planet(index) {
+                if let Some(planet) = scene.solar_system.get_planet(index) {
                     camera.smooth_follow(planet.get_position(), 0.05);
                 }
             }
@@ -196,7 +254,7 @@ fn main() {
             };
 
             if window.is_key_pressed(key, minifb::KeyRepeat::No) {
-                if let Some(planet) = solar_system.get_planet(i) {
+                if let Some(planet) = scene.solar_system.get_planet(i) {
                     _selected_planet = Some(i);
                     camera.set_target(planet.get_position());
                     
@@ -215,7 +273,7 @@ fn main() {
         // ============================================
         if window.is_key_pressed(Key::Tab, minifb::KeyRepeat::No) {
             if let Some(index) = _selected_planet {
-                if let Some(planet) = solar_system.get_planet(index) {
+                if let Some(planet) = scene.solar_system.get_planet(index) {
                     if !warp_effect.is_active() {
                         println!("\n🌀 ¡INICIANDO WARP a {}! ", planet.name);
                         
@@ -281,13 +339,74 @@ fn main() {
             camera.zoom(0.2);
         }
 
+        // Alternar early-z para comparar costo de sombreado
+        if window.is_key_pressed(Key::Z, minifb::KeyRepeat::No) {
+            renderer.early_z = !renderer.early_z;
+            println!("\n⚡ Early-z: {}", if renderer.early_z { "ON" } else { "OFF" });
+        }
+
+        // Tone mapping y exposición
+        if window.is_key_pressed(Key::T, minifb::KeyRepeat::No) {
+            tone_mapper.operator = tone_mapper.operator.next();
+            println!("\n🎞️  Tone mapping: {}", tone_mapper.operator.name());
+        }
+        if window.is_key_down(Key::Equal) || window.is_key_down(Key::NumPadPlus) {
+            tone_mapper.adjust_exposure(1.02);
+        }
+        if window.is_key_down(Key::Minus) || window.is_key_down(Key::NumPadMinus) {
+            tone_mapper.adjust_exposure(1.0 / 1.02);
+        }
+
+        if window.is_key_pressed(Key::B, minifb::KeyRepeat::No) {
+            bloom.enabled = !bloom.enabled;
+            println!("\n✨ Bloom: {}", if bloom.enabled { "ON" } else { "OFF" });
+        }
+
+        if window.is_key_pressed(Key::L, minifb::KeyRepeat::No) {
+            let enabled = scene.light_falloff == Falloff::None;
+            scene.set_inverse_square(enabled);
+            println!("\n💡 Luz con cuadrado inverso: {}", if enabled { "ON" } else { "OFF" });
+        }
+
+        if window.is_key_pressed(Key::H, minifb::KeyRepeat::No) {
+            scene.use_baked_layers = !scene.use_baked_layers;
+            println!("\n🔥 Mapas horneados: {}", if scene.use_baked_layers { "ON" } else { "OFF (capas calculadas por píxel)" });
+        }
+
+        if window.is_key_pressed(Key::P, minifb::KeyRepeat::No) {
+            renderer.depth_mode = renderer.depth_mode.next();
+            println!("\n📏 Profundidad: {}", renderer.depth_mode.name());
+        }
+
+        // Vistas de depuración: cada tecla activa la suya o vuelve al render normal
+        for (key, view) in [
+            (Key::F1, DebugView::Wireframe),
+            (Key::F2, DebugView::Normals),
+            (Key::F3, DebugView::Depth),
+            (Key::F4, DebugView::Uvs),
+            (Key::F5, DebugView::Overdraw),
+        ] {
+            if window.is_key_pressed(key, minifb::KeyRepeat::No) {
+                renderer.debug_view = view.toggle(renderer.debug_view);
+                println!("\n🔍 Vista: {}", renderer.debug_view.name());
+            }
+        }
+
+        // La captura se guarda después de dibujar el frame
+        let take_screenshot = window.is_key_pressed(Key::F12, minifb::KeyRepeat::No);
+
+        if window.is_key_pressed(Key::M, minifb::KeyRepeat::No) {
+            antialiaser.mode = antialiaser.mode.next();
+            println!("\n🔲 Antialiasing: {}", antialiaser.mode.name());
+        }
+
         // Reset cámara
         if window.is_key_pressed(Key::R, minifb::KeyRepeat::No) {
             camera = Camera::new(
                 Vec3::new(0.0, 15.0, 30.0),
                 Vec3::new(0.0, 0.0, 0.0),
                 Vec3::new(0.0, 1.0, 0.0),
-            );
+            ).with_clip_planes(options.near, options.far);
             _selected_planet = None;
             println!("\n📷 Cámara reseteada - Volviendo al Sol ☀️\n");
         }
@@ -297,72 +416,42 @@ fn main() {
         // ============================================
         
         // Colisión con el sol
-        let sun_collision_radius = solar_system.sun.get_scale() * 1.5;
-        if check_sphere_collision(camera.eye, 0.5, solar_system.sun.get_position(), sun_collision_radius) {
+        let sun_collision_radius = scene.solar_system.sun.get_scale() * 0.6;
+        if check_sphere_collision(camera.eye, camera_radius, scene.solar_system.sun.get_position(), sun_collision_radius) {
             camera.eye = resolve_sphere_collision(
                 camera.eye,
-                0.5,
-                solar_system.sun.get_position(),
+                camera_radius,
+                scene.solar_system.sun.get_position(),
                 sun_collision_radius
             );
         }
 
-        // Colisión con planetas
-        for planet in &solar_system.planets {
-            let planet_collision_radius = planet.get_scale() * 1.8;
-            if check_sphere_collision(camera.eye, 0.5, planet.get_position(), planet_collision_radius) {
+        // Colisión con planetas (la esfera del modelo tiene radio 0.5 * escala)
+        for planet in &scene.solar_system.planets {
+            let planet_collision_radius = planet.get_scale() * 0.55;
+            if check_sphere_collision(camera.eye, camera_radius, planet.get_position(), planet_collision_radius) {
                 camera.eye = resolve_sphere_collision(
                     camera.eye,
-                    0.5,
+                    camera_radius,
                     planet.get_position(),
                     planet_collision_radius
                 );
             }
         }
 
-        // 🆕 ============================================
-        // SAFE ZONE - TELEPORT AUTOMÁTICO SI HAY LAG
-        // ============================================
-
-        // Recolectar posiciones de planetas
-        let planet_data: Vec<(Vec3, f32)> = solar_system.planets
-            .iter()
-            .map(|p| (p.get_position(), p.get_scale()))
-            .collect();
-
-        // Recolectar radios de órbitas
-        let orbit_radii: Vec<f32> = solar_system.planets
-            .iter()
-            .map(|p| p.orbit.radius)
-            .collect();
-
-        // Verificar y corregir posición si es necesario
-        if let Some(safe_position) = safe_zone.check_and_correct(
-            camera.eye,
-            &planet_data,
-            &orbit_radii,
-        ) {
-            camera.eye = safe_position;
-            println!("✅ Cámara reubicada a posición segura");
-        }        
-
         // ============================================
         // RENDERIZADO
         // ============================================
-        framebuffer.clear();
-
-        // 🆕 MOSTRAR ADVERTENCIA SI ESTAMOS EN ZONA PELIGROSA
-        if safe_zone.danger_counter > 0 {
-            // Cambiar fondo a rojo si hay peligro
-            let danger_intensity = (safe_zone.danger_counter as f32 / 3.0 * 50.0) as u8;
-            framebuffer.set_background_color(Color::new(
-                20 + danger_intensity,
-                5,
-                5
-            ));
-        } else {
-            framebuffer.set_background_color(Color::new(5, 5, 20));
-        }        
+        // Si cambió el tamaño de la ventana, realocar los buffers; la
+        // proyección y el viewport se arman cada frame con el tamaño actual
+        let (new_width, new_height) = window.get_size();
+        if new_width > 0 && new_height > 0 && (new_width, new_height) != (window_width, window_height) {
+            window_width = new_width;
+            window_height = new_height;
+            let (render_width, render_height) = scaled_size(window_width, window_height, options.render_scale);
+            framebuffer.resize(render_width, render_height);
+            println!("\n🖥️  Ventana {}x{} (render {}x{})", window_width, window_height, render_width, render_height);
+        }
 
         // APLICAR EFECTO VISUAL DE WARP
         let warp_distortion = warp_effect.get_distortion_factor();
@@ -377,161 +466,120 @@ fn main() {
             framebuffer.set_background_color(Color::new(5, 5, 20));
         }
 
-        // Configurar matrices de vista
-        let view_matrix = camera.get_view_matrix();
-        let projection_matrix = camera.get_projection_matrix(SCREEN_WIDTH as f32 / SCREEN_HEIGHT as f32);
-        let viewport_matrix = create_viewport_matrix(SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
+        antialiaser.render(&scene, &mut renderer, &mut framebuffer, &camera, time);
 
-        // 🆕 1. RENDERIZAR ÓRBITAS (solo si estamos lejos del centro)
-        let distance_to_center = camera.eye.magnitude();
-        if distance_to_center > 12.0 && distance_to_center < 60.0 {
-            for (i, orbit_verts) in orbit_lines.iter().enumerate() {
-                if let Some(planet) = solar_system.get_planet(i) {
-                    let distance_to_orbit = (camera.eye - planet.get_position()).magnitude();
-                    
-                    // Solo renderizar órbitas que están a distancia visible
-                    if distance_to_orbit > 3.0 {
-                        let orbit_model_matrix = create_model_matrix(
-                            Vec3::zeros(),
-                            1.0,
-                            Vec3::zeros()
-                        );
-
-                        let orbit_uniforms = Uniforms {
-                            model_matrix: orbit_model_matrix,
-                            view_matrix,
-                            projection_matrix,
-                            viewport_matrix,
-                            time,
-                            light_dir: Vec3::new(0.0, 1.0, 0.0),
-                        };
-
-                        render(&mut framebuffer, &orbit_uniforms, orbit_verts, "orbit");
-                    }
-                }
-            }
+        // Durante el warp todo brilla más: umbral más bajo y más intensidad
+        let mut frame_bloom = bloom;
+        frame_bloom.threshold *= 1.0 - warp_distortion * 0.5;
+        frame_bloom.intensity *= 1.0 + warp_distortion * 2.0;
+
+        // Las vistas de depuración muestran sus valores tal cual
+        if renderer.debug_view.replaces_output() {
+            framebuffer.resolve(&ToneMapper::new(ToneMapping::Clamp, 1.0));
+        } else {
+            frame_bloom.apply(&mut framebuffer);
+            framebuffer.resolve(&tone_mapper);
         }
 
-        // 🆕 2. RENDERIZAR EL SOL (con culling)
-        let sun = &solar_system.sun;
-        let distance_to_sun = (camera.eye - sun.get_position()).magnitude();
-        
-        if distance_to_sun > sun.get_scale() * 3.0 {
-            let sun_model_matrix = create_model_matrix(
-                sun.get_position(),
-                sun.get_scale(),
-                sun.get_rotation()
-            );
+        // FPS y contadores de sombreado en el título, dos veces por segundo
+        frames_since_title += 1;
+        let title_elapsed = title_timer.elapsed().as_secs_f32();
+        if title_elapsed >= 0.5 {
+            let stats = &renderer.stats;
+            let recording = recorder.as_ref()
+                .map(|recording| format!("● REC {} | ", recording.frames()))
+                .unwrap_or_default();
+            window.set_title(&format!(
+                "{}Sistema Solar - Proyecto 3 | {:.0} FPS | {}x{} | vista {} | {} | {} | {} x{:.2} | objetos {}/{} | early-z {} | shaders {}/{} ({:.0}% ahorrado)",
+                recording,
+                frames_since_title as f32 / title_elapsed,
+                framebuffer.width,
+                framebuffer.height,
+                renderer.debug_view.name(),
+                antialiaser.mode.name(),
+                renderer.depth_mode.name(),
+                tone_mapper.operator.name(),
+                tone_mapper.exposure,
+                stats.objects_drawn,
+                stats.objects_drawn + stats.objects_frustum_culled,
+                if renderer.early_z { "ON" } else { "OFF" },
+                stats.shader_invocations,
+                stats.fragments,
+                stats.saved_percentage(),
+            ));
+            title_timer = Instant::now();
+            frames_since_title = 0;
+        }
 
-            let sun_uniforms = Uniforms {
-                model_matrix: sun_model_matrix,
-                view_matrix,
-                projection_matrix,
-                viewport_matrix,
-                time,
-                light_dir: Vec3::new(0.0, 0.0, 0.0),
+        // CAPTURA (F12): lo que se ve, o el mismo frame re-renderizado a mayor resolución
+        if take_screenshot {
+            let saved = if screenshots.scale > 1 {
+                let scale = screenshots.scale;
+                let mut offscreen = Framebuffer::new(window_width * scale, window_height * scale);
+                offscreen.set_background_color(framebuffer.background_color);
+
+                // Un píxel de la ventana son `scale` píxeles de la captura (líneas, puntos)
+                let window_render_scale = renderer.render_scale;
+                renderer.render_scale = scale as f32;
+                AntiAliaser::new(antialiaser.mode).render(&scene, &mut renderer, &mut offscreen, &camera, time);
+                renderer.render_scale = window_render_scale;
+
+                if renderer.debug_view.replaces_output() {
+                    offscreen.resolve(&ToneMapper::new(ToneMapping::Clamp, 1.0));
+                } else {
+                    // Más niveles para que el resplandor cubra lo mismo que en la ventana
+                    let mut capture_bloom = frame_bloom;
+                    capture_bloom.levels += (scale as f32).log2().round() as usize;
+                    capture_bloom.apply(&mut offscreen);
+                    offscreen.resolve(&tone_mapper);
+                }
+                screenshots.save(&offscreen.buffer, offscreen.width, offscreen.height)
+            } else if framebuffer.width == window_width && framebuffer.height == window_height {
+                screenshots.save(&framebuffer.buffer, window_width, window_height)
+            } else {
+                framebuffer.upscale_into(&mut display_buffer, window_width, window_height);
+                screenshots.save(&display_buffer, window_width, window_height)
             };
 
-            render(&mut framebuffer, &sun_uniforms, &sphere_model.vertices, sun.get_shader());
+            match saved {
+                Ok(path) => println!("\n📸 Captura guardada en {}", path.display()),
+                Err(e) => eprintln!("\n❌ {}", e),
+            }
         }
 
-        // 🆕 3. RENDERIZAR TODOS LOS PLANETAS (con culling)
-        let min_render_distance = 3.0;
-        for planet in &solar_system.planets {
-            let distance_to_planet = (camera.eye - planet.get_position()).magnitude();
-            if distance_to_planet < min_render_distance {
-                continue;  // Saltar este planeta
+        if let Some(recording) = recorder.as_mut() {
+            let recorded = recording.record(&framebuffer);
+            if let Err(e) = &recorded {
+                eprintln!("\n❌ {}", e);
             }
-            
-            // Solo renderizar si no estamos muy cerca
-            if distance_to_planet > planet.get_scale() * 2.5 {
-                let planet_model_matrix = create_model_matrix(
-                    planet.get_position(),
-                    planet.get_scale(),
-                    planet.get_rotation()
-                );
-
-                let planet_uniforms = Uniforms {
-                    model_matrix: planet_model_matrix,
-                    view_matrix,
-                    projection_matrix,
-                    viewport_matrix,
-                    time,
-                    light_dir: Vec3::new(1.0, 1.0, 1.0).normalize(),
-                };
-
-                render(&mut framebuffer, &planet_uniforms, &sphere_model.vertices, planet.get_shader());
-
-                // Renderizar satélites (Luna)
-                for satellite in &planet.satellites {
-                    let distance_to_satellite = (camera.eye - satellite.get_position()).magnitude();
-                    
-                    if distance_to_satellite > satellite.get_scale() * 1.5 {
-                        let sat_model_matrix = create_model_matrix(
-                            satellite.get_position(),
-                            satellite.get_scale(),
-                            satellite.get_rotation()
-                        );
-
-                        let sat_uniforms = Uniforms {
-                            model_matrix: sat_model_matrix,
-                            view_matrix,
-                            projection_matrix,
-                            viewport_matrix,
-                            time,
-                            light_dir: Vec3::new(1.0, 1.0, 1.0).normalize(),
-                        };
-
-                        render(&mut framebuffer, &sat_uniforms, &sphere_model.vertices, satellite.get_shader());
-                    }
-                }
-
-                // Renderizar anillos de Saturno
-                if planet.name == "Saturno" && distance_to_planet > planet.get_scale() * 1.5 {
-                    let ring_rotation = Vec3::new(0.4, planet.rotation.y, 0.0);
-                    let ring_model_matrix = create_model_matrix(
-                        planet.get_position(),
-                        planet.get_scale(),
-                        ring_rotation
-                    );
-
-                    let ring_uniforms = Uniforms {
-                        model_matrix: ring_model_matrix,
-                        view_matrix,
-                        projection_matrix,
-                        viewport_matrix,
-                        time,
-                        light_dir: Vec3::new(1.0, 1.0, 1.0).normalize(),
-                    };
-
-                    render(&mut framebuffer, &ring_uniforms, &ring_vertices, "ring");
+            if recorded.is_err() || recording.is_full() {
+                if let Some(recording) = recorder.take() {
+                    time_offset = finish_recording(recording) - start_time.elapsed().as_secs_f32();
                 }
             }
         }
 
-        // 4. RENDERIZAR NAVE ESPACIAL (siempre visible)
-        let ship_position = spaceship.get_position(&camera);
-        let ship_rotation = spaceship.get_rotation(&camera);
-        
-        let ship_model_matrix = create_model_matrix(
-            ship_position,
-            spaceship.scale,
-            ship_rotation
-        );
-
-        let ship_uniforms = Uniforms {
-            model_matrix: ship_model_matrix,
-            view_matrix, 
-            projection_matrix,
-            viewport_matrix,
-            time,
-            light_dir: Vec3::new(1.0, 1.0, 1.0).normalize(),
-        };
-
-        render(&mut framebuffer, &ship_uniforms, &spaceship.model.vertices, "spaceship");
+        if framebuffer.width == window_width && framebuffer.height == window_height {
+            window
+                .update_with_buffer(&framebuffer.buffer, window_width, window_height)
+                .unwrap();
+        } else {
+            framebuffer.upscale_into(&mut display_buffer, window_width, window_height);
+            window
+                .update_with_buffer(&display_buffer, window_width, window_height)
+                .unwrap();
+        }
+    }
+}
 
-        window
-            .update_with_buffer(&framebuffer.buffer, SCREEN_WIDTH, SCREEN_HEIGHT)
-            .unwrap();
+/// Cierra la grabación, informa cuánto video quedó y devuelve el tiempo del
+/// último frame grabado, para que el reloj siga desde ahí
+fn finish_recording(recording: Recorder) -> f32 {
+    let (frames, fps, time) = (recording.frames(), recording.fps, recording.time());
+    match recording.finish() {
+        Ok(path) => println!("\n⏹️  Grabación terminada: {} frames ({:.2} s a {} fps) en {}",
+                             frames, frames as f32 / fps, fps, path.display()),
+        Err(e) => eprintln!("\n❌ {}", e),
     }
-}
\ No newline at end of file
+    time
+}
@@ -1,10 +1,25 @@
 use nalgebra_glm::{Vec2, Vec3};
 use crate::vertex::Vertex;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+/// Malla indexada: vértices únicos y una lista de triángulos (tres índices
+/// por triángulo). Así cada vértice compartido se transforma una sola vez.
 pub struct Model {
     pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+/// Clave para deduplicar vértices: los bits exactos de posición, normal y UV
+type VertexKey = [u32; 8];
+
+fn vertex_key(position: &Vec3, normal: &Vec3, tex_coords: &Vec2) -> VertexKey {
+    [
+        position.x.to_bits(), position.y.to_bits(), position.z.to_bits(),
+        normal.x.to_bits(), normal.y.to_bits(), normal.z.to_bits(),
+        tex_coords.x.to_bits(), tex_coords.y.to_bits(),
+    ]
 }
 
 impl Model {
@@ -17,6 +32,8 @@ impl Model {
         let mut temp_normals = Vec::new();
         let mut temp_texcoords = Vec::new();
         let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut unique: HashMap<VertexKey, u32> = HashMap::new();
 
         for line in reader.lines() {
             let line = line.map_err(|e| format!("Error leyendo línea: {}", e))?;
@@ -28,35 +45,29 @@ impl Model {
 
             let parts: Vec<&str> = line.split_whitespace().collect();
 
-            match parts.get(0) {
-                Some(&"v") => {
-                    if parts.len() >= 4 {
-                        let x: f32 = parts[1].parse().unwrap_or(0.0);
-                        let y: f32 = parts[2].parse().unwrap_or(0.0);
-                        let z: f32 = parts[3].parse().unwrap_or(0.0);
-                        temp_positions.push(Vec3::new(x, y, z));
-                    }
+            match parts.first() {
+                Some(&"v") if parts.len() >= 4 => {
+                    let x: f32 = parts[1].parse().unwrap_or(0.0);
+                    let y: f32 = parts[2].parse().unwrap_or(0.0);
+                    let z: f32 = parts[3].parse().unwrap_or(0.0);
+                    temp_positions.push(Vec3::new(x, y, z));
                 }
-                Some(&"vn") => {
-                    if parts.len() >= 4 {
-                        let x: f32 = parts[1].parse().unwrap_or(0.0);
-                        let y: f32 = parts[2].parse().unwrap_or(0.0);
-                        let z: f32 = parts[3].parse().unwrap_or(0.0);
-                        temp_normals.push(Vec3::new(x, y, z));
-                    }
+                Some(&"vn") if parts.len() >= 4 => {
+                    let x: f32 = parts[1].parse().unwrap_or(0.0);
+                    let y: f32 = parts[2].parse().unwrap_or(0.0);
+                    let z: f32 = parts[3].parse().unwrap_or(0.0);
+                    temp_normals.push(Vec3::new(x, y, z));
                 }
-                Some(&"vt") => {
-                    if parts.len() >= 3 {
-                        let u: f32 = parts[1].parse().unwrap_or(0.0);
-                        let v: f32 = parts[2].parse().unwrap_or(0.0);
-                        temp_texcoords.push(Vec2::new(u, v));
-                    }
+                Some(&"vt") if parts.len() >= 3 => {
+                    let u: f32 = parts[1].parse().unwrap_or(0.0);
+                    let v: f32 = parts[2].parse().unwrap_or(0.0);
+                    temp_texcoords.push(Vec2::new(u, v));
                 }
                 Some(&"f") => {
                     let mut face_vertices = Vec::new();
 
-                    for i in 1..parts.len() {
-                        let indices: Vec<&str> = parts[i].split('/').collect();
+                    for part in parts.iter().skip(1) {
+                        let indices: Vec<&str> = part.split('/').collect();
 
                         let pos_idx: usize = indices[0].parse::<usize>().unwrap_or(1) - 1;
                         let tex_idx: usize = if indices.len() > 1 && !indices[1].is_empty() {
@@ -74,21 +85,40 @@ impl Model {
                         let normal = temp_normals.get(norm_idx).cloned().unwrap_or(Vec3::new(0.0, 1.0, 0.0));
                         let tex_coords = temp_texcoords.get(tex_idx).cloned().unwrap_or(Vec2::zeros());
 
-                        face_vertices.push(Vertex::new(position, normal, tex_coords));
+                        // Las combinaciones v/vt/vn repetidas reutilizan el mismo vértice
+                        let index = *unique
+                            .entry(vertex_key(&position, &normal, &tex_coords))
+                            .or_insert_with(|| {
+                                vertices.push(Vertex::new(position, normal, tex_coords));
+                                (vertices.len() - 1) as u32
+                            });
+                        face_vertices.push(index);
                     }
 
                     // Triangulación en abanico
-                    for i in 1..(face_vertices.len() - 1) {
-                        vertices.push(face_vertices[0].clone());
-                        vertices.push(face_vertices[i].clone());
-                        vertices.push(face_vertices[i + 1].clone());
+                    for i in 1..face_vertices.len().saturating_sub(1) {
+                        indices.extend_from_slice(&[face_vertices[0], face_vertices[i], face_vertices[i + 1]]);
                     }
                 }
                 _ => {}
             }
         }
 
-        Ok(Model { vertices })
+        Ok(Model { vertices, indices })
+    }
+
+    /// Cantidad de triángulos de la malla
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Distancia máxima de un vértice al origen del modelo (radio de la esfera
+    /// envolvente antes de escalar)
+    pub fn bounding_radius(&self) -> f32 {
+        self.vertices
+            .iter()
+            .map(|vertex| vertex.position.magnitude())
+            .fold(0.0, f32::max)
     }
 
     pub fn get_bounds(&self) -> (Vec3, Vec3) {
@@ -0,0 +1,410 @@
+use crate::antialiasing::AntiAliasing;
+use crate::camera::{DEFAULT_FAR, DEFAULT_NEAR};
+use crate::debug_view::DebugView;
+use crate::depth::DepthMode;
+use crate::headless::MAX_SIMULATION_TIME;
+use crate::image_io::ImageFormat;
+use crate::render::Renderer;
+use crate::recording::{frame_rate, RecordFormat};
+use crate::texture::Filter;
+use crate::bake::DEFAULT_BAKE_WIDTH;
+use crate::tonemap::ToneMapping;
+use nalgebra_glm::Vec3;
+use std::path::PathBuf;
+
+/// Opciones de línea de comandos
+pub struct Options {
+    pub show_help: bool,
+    pub headless: bool,
+    pub eye: Vec3,
+    pub target: Vec3,
+    pub width: usize,
+    pub height: usize,
+    pub time: f32,
+    pub frames: usize,
+    pub fps: f32,
+    pub output_dir: PathBuf,
+    pub format: ImageFormat,
+    pub show_spaceship: bool,
+    pub threads: usize,
+    pub early_z: bool,
+    pub tone_mapping: ToneMapping,
+    pub exposure: f32,
+    pub bloom: bool,
+    pub bloom_threshold: f32,
+    pub bloom_intensity: f32,
+    pub antialiasing: AntiAliasing,
+    pub inverse_square: bool,
+    pub render_scale: f32,
+    pub depth_mode: DepthMode,
+    pub near: f32,
+    pub far: f32,
+    pub depth_test_scene: bool,
+    pub debug_view: DebugView,
+    pub surface_maps: Vec<SurfaceMapSpec>,
+    pub texture_filter: Filter,
+    pub bake: bool,
+    pub bake_resolution: usize,
+    pub bake_cache: Option<PathBuf>,
+    pub screenshot_dir: PathBuf,
+    pub screenshot_format: ImageFormat,
+    pub screenshot_scale: usize,
+    pub record: bool,
+    pub record_dir: PathBuf,
+    pub record_format: RecordFormat,
+    pub record_fps: f32,
+    pub record_frames: Option<usize>,
+}
+
+/// Mapa de superficie pedido por línea de comandos: `CUERPO=RUTA[:MEZCLA]`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurfaceMapSpec {
+    pub body: String,
+    pub path: PathBuf,
+    pub blend: f32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            show_help: false,
+            headless: false,
+            eye: Vec3::new(0.0, 15.0, 30.0),
+            target: Vec3::new(0.0, 0.0, 0.0),
+            width: 800,
+            height: 600,
+            time: 0.0,
+            frames: 1,
+            fps: 60.0,
+            output_dir: PathBuf::from("renders"),
+            format: ImageFormat::Png,
+            show_spaceship: true,
+            threads: Renderer::default_threads(),
+            early_z: true,
+            tone_mapping: ToneMapping::AcesFilmic,
+            exposure: 1.0,
+            bloom: true,
+            bloom_threshold: 1.0,
+            bloom_intensity: 0.8,
+            antialiasing: AntiAliasing::Off,
+            inverse_square: false,
+            render_scale: 1.0,
+            depth_mode: DepthMode::Standard,
+            near: DEFAULT_NEAR,
+            far: DEFAULT_FAR,
+            depth_test_scene: false,
+            debug_view: DebugView::Off,
+            surface_maps: Vec::new(),
+            texture_filter: Filter::Trilinear,
+            bake: true,
+            bake_resolution: DEFAULT_BAKE_WIDTH,
+            bake_cache: None,
+            screenshot_dir: PathBuf::from("screenshots"),
+            screenshot_format: ImageFormat::Png,
+            screenshot_scale: 1,
+            record: false,
+            record_dir: PathBuf::from("recordings"),
+            record_format: RecordFormat::Y4m,
+            record_fps: 60.0,
+            record_frames: None,
+        }
+    }
+}
+
+pub const USAGE: &str = "\
+Uso: proyecto3 [opciones]
+
+  --headless             Renderizar sin ventana y guardar imágenes
+  --eye X,Y,Z            Posición de la cámara (default 0,15,30)
+  --target X,Y,Z         Punto al que mira la cámara (default 0,0,0)
+  --width N              Ancho de la ventana o imagen en píxeles (default 800)
+  --height N             Alto de la ventana o imagen en píxeles (default 600)
+  --render-scale N       Fracción de la resolución a la que se renderiza,
+                         escalando después a la ventana (0.25-1, default 1)
+  --time SEGUNDOS        Tiempo de simulación del primer frame (default 0,
+                         el último no puede pasar de un día: 86400)
+  --frames N             Cantidad de frames a generar (default 1)
+  --fps N                Frames por segundo de la secuencia (default 60)
+  --output DIR           Directorio de salida (default renders)
+  --format png|ppm       Formato de imagen (default png)
+  --no-ship              No dibujar la nave espacial
+  --threads N            Hilos del rasterizador (default: todos los núcleos)
+  --no-early-z           Sombrear cada fragmento (sin G-buffer de visibilidad)
+  --tonemap none|reinhard|aces
+                         Operador de tone mapping (default aces)
+  --exposure N           Exposición lineal antes del tone mapping (default 1)
+  --no-bloom             Desactivar el resplandor de las zonas brillantes
+  --bloom-threshold N    Luminancia mínima que brilla (default 1)
+  --bloom-intensity N    Intensidad del resplandor (default 0.8)
+  --aa MODO              Antialiasing: none, msaa2, msaa4, msaa8,
+                         ssaa2, ssaa3, ssaa4 (default none)
+  --inverse-square       Atenuar la luz del sol con el cuadrado de la distancia
+  --depth standard|reversed
+                         Profundidad guardada: z/w o 1/w en float (default standard)
+  --near N               Distancia del plano de recorte cercano (default 0.1)
+  --far N                Distancia del plano de recorte lejano (default 500)
+  --depth-test-scene     Escena de prueba de z-fighting: una luna a 0.1
+                         unidades de su planeta (probar con --near 0.00003)
+  --debug-view MODO      Vista de depuración: none, wireframe, normals,
+                         depth, uv, overdraw (default none)
+  --surface-map CUERPO=RUTA[:MEZCLA]
+                         Textura equirectangular (png, ppm o tga) sobre un
+                         cuerpo; MEZCLA 0-1 es cuánto reemplaza al shader
+                         (default 1). Se puede repetir
+  --texture-filter nearest|bilinear|trilinear
+                         Filtrado de las texturas (default trilinear)
+  --no-bake              Calcular todas las capas de los shaders por píxel
+                         en vez de hornear las estáticas en mapas
+  --bake-resolution N    Ancho de los mapas horneados; el alto es la mitad
+                         (default 1024)
+  --bake-cache DIR       Guardar los mapas horneados en DIR y reusarlos
+  --screenshot-dir DIR   Directorio de las capturas de F12 (default screenshots)
+  --screenshot-format png|ppm
+                         Formato de las capturas (default png)
+  --screenshot-scale N   Capturar a N veces la resolución de la ventana,
+                         re-renderizando el frame (1-4, default 1)
+  --record               Empezar a grabar al abrir la ventana (como F9)
+  --record-dir DIR       Directorio de las grabaciones (default recordings)
+  --record-format y4m|png|ppm
+                         Video Y4M sin comprimir o frames numerados
+                         (default y4m)
+  --record-fps N         Frames por segundo de la grabación; cada frame
+                         avanza 1/N s de simulación (default 60)
+  --record-frames N      Terminar cada grabación después de N frames
+  --help                 Mostrar esta ayuda";
+
+impl Options {
+    pub fn from_args() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut options = Options::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => options.headless = true,
+                "--no-ship" => options.show_spaceship = false,
+                "--no-early-z" => options.early_z = false,
+                "--eye" => options.eye = parse_vec3(&next_value(&mut args, &arg)?)?,
+                "--target" => options.target = parse_vec3(&next_value(&mut args, &arg)?)?,
+                "--width" => options.width = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--height" => options.height = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--time" => options.time = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--frames" => options.frames = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--fps" => options.fps = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--threads" => options.threads = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--output" => options.output_dir = PathBuf::from(next_value(&mut args, &arg)?),
+                "--format" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.format = ImageFormat::from_name(&value)
+                        .ok_or_else(|| format!("Formato desconocido: {}", value))?;
+                }
+                "--tonemap" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.tone_mapping = ToneMapping::from_name(&value)
+                        .ok_or_else(|| format!("Tone mapping desconocido: {}", value))?;
+                }
+                "--exposure" => options.exposure = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--no-bloom" => options.bloom = false,
+                "--bloom-threshold" => options.bloom_threshold = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--bloom-intensity" => options.bloom_intensity = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--aa" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.antialiasing = AntiAliasing::from_name(&value)
+                        .ok_or_else(|| format!("Antialiasing desconocido: {}", value))?;
+                }
+                "--inverse-square" => options.inverse_square = true,
+                "--depth" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.depth_mode = DepthMode::from_name(&value)
+                        .ok_or_else(|| format!("Modo de profundidad desconocido: {}", value))?;
+                }
+                "--near" => options.near = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--far" => options.far = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--depth-test-scene" => options.depth_test_scene = true,
+                "--debug-view" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.debug_view = DebugView::from_name(&value)
+                        .ok_or_else(|| format!("Vista de depuración desconocida: {}", value))?;
+                }
+                "--surface-map" => options.surface_maps.push(parse_surface_map(&next_value(&mut args, &arg)?)?),
+                "--texture-filter" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.texture_filter = Filter::from_name(&value)
+                        .ok_or_else(|| format!("Filtro de textura desconocido: {}", value))?;
+                }
+                "--no-bake" => options.bake = false,
+                "--bake-resolution" => options.bake_resolution = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--bake-cache" => options.bake_cache = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+                "--screenshot-dir" => options.screenshot_dir = PathBuf::from(next_value(&mut args, &arg)?),
+                "--screenshot-format" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.screenshot_format = ImageFormat::from_name(&value)
+                        .ok_or_else(|| format!("Formato desconocido: {}", value))?;
+                }
+                "--screenshot-scale" => options.screenshot_scale = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--record" => options.record = true,
+                "--record-dir" => options.record_dir = PathBuf::from(next_value(&mut args, &arg)?),
+                "--record-format" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.record_format = RecordFormat::from_name(&value)
+                        .ok_or_else(|| format!("Formato desconocido: {}", value))?;
+                }
+                "--record-fps" => options.record_fps = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--record-frames" => options.record_frames = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?),
+                "--render-scale" => options.render_scale = parse_number(&next_value(&mut args, &arg)?, &arg)?,
+                "--help" | "-h" => options.show_help = true,
+                _ => return Err(format!("Opción desconocida: {}\n\n{}", arg, USAGE)),
+            }
+        }
+
+        if options.width == 0 || options.height == 0 {
+            return Err("La resolución debe ser mayor que cero".to_string());
+        }
+        if options.threads == 0 {
+            return Err("--threads debe ser al menos 1".to_string());
+        }
+        // `parse` acepta "NaN" e "inf", que dejarían la imagen en negro o en blanco
+        if !(options.exposure > 0.0 && options.exposure.is_finite()) {
+            return Err("--exposure debe ser un número finito mayor que cero".to_string());
+        }
+        // Un NaN en el umbral o la intensidad se esparce por todo el buffer HDR
+        let valid_bloom = |value: f32| value >= 0.0 && value.is_finite();
+        if !valid_bloom(options.bloom_threshold) || !valid_bloom(options.bloom_intensity) {
+            return Err("Los parámetros de bloom deben ser números finitos no negativos".to_string());
+        }
+        if !(0.25..=1.0).contains(&options.render_scale) {
+            return Err("--render-scale debe estar entre 0.25 y 1".to_string());
+        }
+        // NaN no cumple ninguna comparación, así que se exige la condición válida
+        if !(options.near > 0.0 && options.far > options.near && options.far.is_finite()) {
+            return Err("Los planos de recorte deben ser finitos y cumplir 0 < --near < --far".to_string());
+        }
+        if !(16..=16384).contains(&options.bake_resolution) {
+            return Err("--bake-resolution debe estar entre 16 y 16384".to_string());
+        }
+        if !(1..=4).contains(&options.screenshot_scale) {
+            return Err("--screenshot-scale debe estar entre 1 y 4".to_string());
+        }
+        if !(options.fps > 0.0 && options.fps.is_finite()) {
+            return Err("--fps debe ser un número finito mayor que cero".to_string());
+        }
+        // Headless simula con pasos fijos hasta el último frame antes de dibujarlo
+        let last_frame_time = options.time + options.frames.saturating_sub(1) as f32 / options.fps;
+        if !last_frame_time.is_finite() || last_frame_time.abs() > MAX_SIMULATION_TIME {
+            return Err(format!(
+                "--time y el último frame deben quedar dentro de ±{} s de simulación",
+                MAX_SIMULATION_TIME
+            ));
+        }
+        if frame_rate(options.record_fps).is_none() {
+            return Err("--record-fps debe ser un número finito de al menos 0.001".to_string());
+        }
+        if options.record_frames == Some(0) {
+            return Err("--record-frames debe ser al menos 1".to_string());
+        }
+
+        Ok(options)
+    }
+}
+
+fn next_value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("Falta el valor para {}", flag))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, flag: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Valor inválido para {}: {}", flag, value))
+}
+
+/// `CUERPO=RUTA[:MEZCLA]`; la mezcla solo se toma si lo que sigue al último
+/// `:` es un número, así las rutas con `:` siguen funcionando
+fn parse_surface_map(value: &str) -> Result<SurfaceMapSpec, String> {
+    let (body, rest) = value
+        .split_once('=')
+        .filter(|(body, rest)| !body.is_empty() && !rest.is_empty())
+        .ok_or_else(|| format!("--surface-map espera CUERPO=RUTA[:MEZCLA], no {}", value))?;
+
+    let (path, blend) = match rest.rsplit_once(':').and_then(|(path, blend)| Some((path, blend.parse::<f32>().ok()?))) {
+        Some((path, blend)) => (path, blend),
+        None => (rest, 1.0),
+    };
+    if !(0.0..=1.0).contains(&blend) {
+        return Err(format!("La mezcla de --surface-map debe estar entre 0 y 1: {}", value));
+    }
+
+    Ok(SurfaceMapSpec { body: body.to_string(), path: PathBuf::from(path), blend })
+}
+
+fn parse_vec3(value: &str) -> Result<Vec3, String> {
+    let parts: Vec<f32> = value
+        .split(',')
+        .map(|p| p.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Vector inválido: {} (se espera X,Y,Z)", value))?;
+
+    match parts.as_slice() {
+        [x, y, z] => Ok(Vec3::new(*x, *y, *z)),
+        _ => Err(format!("Vector inválido: {} (se espera X,Y,Z)", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn clip_planes_must_be_finite_and_ordered() {
+        assert!(parse(&["--near", "0.00003", "--far", "1000"]).is_ok());
+        for args in [
+            ["--near", "NaN", "--far", "1000"],
+            ["--near", "0.1", "--far", "NaN"],
+            ["--near", "0.1", "--far", "inf"],
+            ["--near", "0", "--far", "1000"],
+            ["--near", "10", "--far", "1"],
+        ] {
+            assert!(parse(&args).is_err(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn bloom_parameters_must_be_finite_and_non_negative() {
+        assert!(parse(&["--bloom-threshold", "0", "--bloom-intensity", "2.5"]).is_ok());
+        for value in ["NaN", "inf", "-1"] {
+            assert!(parse(&["--bloom-threshold", value]).is_err(), "umbral {}", value);
+            assert!(parse(&["--bloom-intensity", value]).is_err(), "intensidad {}", value);
+        }
+    }
+
+    #[test]
+    fn headless_time_and_fps_must_be_finite_and_bounded() {
+        assert!(parse(&["--time", "10", "--frames", "120", "--fps", "30"]).is_ok());
+        for args in [
+            ["--time", "NaN", "--fps", "30"],
+            ["--time", "inf", "--fps", "30"],
+            ["--time", "1e12", "--fps", "30"],
+            ["--time", "0", "--fps", "NaN"],
+            ["--time", "0", "--fps", "inf"],
+            ["--time", "0", "--fps", "0"],
+        ] {
+            assert!(parse(&args).is_err(), "{:?}", args);
+        }
+
+        // El último frame también cuenta: 10 millones de frames a 30 fps
+        assert!(parse(&["--frames", "10000000", "--fps", "30"]).is_err());
+    }
+
+    #[test]
+    fn record_fps_must_fit_a_y4m_header() {
+        assert!(parse(&["--record-fps", "29.97"]).is_ok());
+        for fps in ["0", "NaN", "inf", "0.0001", "1e12"] {
+            assert!(parse(&["--record-fps", fps]).is_err(), "{}", fps);
+        }
+    }
+}
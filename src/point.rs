@@ -0,0 +1,141 @@
+use crate::color::HdrColor;
+use crate::vertex::Vertex;
+
+/// Tamaño de un punto: fijo en píxeles de la ventana o en unidades de mundo
+/// (radio), que se achica con la distancia como una esfera real
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PointSize {
+    Pixels(f32),
+    World(f32),
+}
+
+/// Forma del punto en pantalla
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PointShape {
+    /// Disco de bordes suaves
+    Disc,
+    /// Cuadrado alineado a la pantalla (billboard). Ningún objeto de la escena
+    /// lo usa todavía.
+    #[cfg_attr(not(test), allow(dead_code))]
+    Quad,
+}
+
+/// Punto ya proyectado: centro en pantalla, radio en píxeles del framebuffer
+pub struct Point {
+    pub center: Vertex,
+    pub radius: f32,
+    pub shape: PointShape,
+}
+
+/// Píxel tocado por un punto
+#[derive(Clone, Copy, Debug)]
+pub struct PointSample {
+    pub x: i32,
+    pub y: i32,
+    pub depth: f32,
+    pub coverage: f32,
+    pub color: HdrColor,
+}
+
+impl Point {
+    pub fn new(center: Vertex, radius: f32, shape: PointShape) -> Self {
+        Point { center, radius, shape }
+    }
+
+    /// Radio usado para rasterizar. Los puntos de menos de medio píxel se
+    /// dibujan de medio píxel con la cobertura reducida en proporción al área,
+    /// así no parpadean ni desaparecen al alejarse.
+    fn raster_radius(&self) -> (f32, f32) {
+        if self.radius >= 0.5 {
+            (self.radius, 1.0)
+        } else {
+            let ratio = self.radius / 0.5;
+            (0.5, ratio * ratio)
+        }
+    }
+
+    pub fn view_depth(&self) -> f32 {
+        self.center.clip_position.w
+    }
+
+    pub fn bounding_box(&self) -> (i32, i32, i32, i32) {
+        let p = self.center.transformed_position;
+        let pad = self.raster_radius().0 + 1.0;
+
+        (
+            (p.x - pad).floor() as i32,
+            (p.y - pad).floor() as i32,
+            (p.x + pad).floor() as i32,
+            (p.y + pad).floor() as i32,
+        )
+    }
+
+    /// Recorre los píxeles dentro de `rect` con cobertura distinta de cero.
+    /// Todo el punto comparte la profundidad y el color de su centro.
+    pub fn rasterize<F: FnMut(PointSample)>(&self, rect: (i32, i32, i32, i32), mut emit: F) {
+        let p = self.center.transformed_position;
+        let (radius, area_scale) = self.raster_radius();
+        let color = HdrColor::from(self.center.color);
+
+        let (box_min_x, box_min_y, box_max_x, box_max_y) = self.bounding_box();
+        let min_x = box_min_x.max(rect.0);
+        let min_y = box_min_y.max(rect.1);
+        let max_x = box_max_x.min(rect.2);
+        let max_y = box_max_y.min(rect.3);
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let dx = x as f32 + 0.5 - p.x;
+                let dy = y as f32 + 0.5 - p.y;
+
+                let coverage = match self.shape {
+                    PointShape::Disc => (radius + 0.5 - (dx * dx + dy * dy).sqrt()).clamp(0.0, 1.0),
+                    PointShape::Quad => {
+                        (radius + 0.5 - dx.abs()).clamp(0.0, 1.0) * (radius + 0.5 - dy.abs()).clamp(0.0, 1.0)
+                    }
+                } * area_scale;
+
+                if coverage > 0.0 {
+                    emit(PointSample { x, y, depth: p.z, coverage, color });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::{Vec2, Vec3};
+
+    fn coverage(shape: PointShape) -> Vec<f32> {
+        let mut center = Vertex::new(Vec3::zeros(), Vec3::new(0.0, 0.0, 1.0), Vec2::zeros());
+        center.transformed_position = Vec3::new(5.0, 5.0, 0.5);
+
+        let mut pixels = vec![0.0; 100];
+        Point::new(center, 2.0, shape).rasterize((0, 0, 9, 9), |sample| {
+            pixels[(sample.y * 10 + sample.x) as usize] += sample.coverage;
+        });
+        pixels
+    }
+
+    #[test]
+    fn discs_and_quads_cover_their_area() {
+        let disc = coverage(PointShape::Disc);
+        let quad = coverage(PointShape::Quad);
+
+        // El centro queda cubierto por completo con ambas formas
+        assert_eq!(disc[4 * 10 + 4], 1.0);
+        assert_eq!(quad[4 * 10 + 4], 1.0);
+
+        // La esquina del cuadrado está fuera del disco
+        assert_eq!(quad[3 * 10 + 3], 1.0);
+        assert!(disc[3 * 10 + 3] < 0.5);
+
+        // La cobertura total se acerca al área de cada forma
+        let disc_area: f32 = disc.iter().sum();
+        let quad_area: f32 = quad.iter().sum();
+        assert!((disc_area - std::f32::consts::PI * 4.0).abs() < 1.0, "disco: {}", disc_area);
+        assert!((quad_area - 16.0).abs() < 1.0, "cuadrado: {}", quad_area);
+    }
+}
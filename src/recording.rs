@@ -0,0 +1,320 @@
+use crate::framebuffer::Framebuffer;
+use crate::headless::SIMULATION_STEP;
+use crate::image_io::{write_image, ImageFormat};
+use crate::options::Options;
+use crate::screenshot::{timestamp, unique_path};
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Salida de una grabación: un directorio de frames numerados o un solo
+/// archivo Y4M que se le puede pasar directo a un encoder
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordFormat {
+    Frames(ImageFormat),
+    Y4m,
+}
+
+impl RecordFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "y4m" => Some(RecordFormat::Y4m),
+            other => ImageFormat::from_name(other).map(RecordFormat::Frames),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecordFormat::Frames(format) => format.extension(),
+            RecordFormat::Y4m => "y4m",
+        }
+    }
+}
+
+enum Sink {
+    Frames(ImageFormat),
+    Y4m(Y4mWriter),
+}
+
+/// Grabación determinista: cada frame avanza exactamente `1 / fps` segundos
+/// de simulación, tarde lo que tarde en dibujarse, y se escribe a disco con
+/// la resolución que tenía la ventana al empezar
+pub struct Recorder {
+    pub path: PathBuf,
+    pub width: usize,
+    pub height: usize,
+    pub fps: f32,
+    start_time: f32,
+    frames: usize,
+    max_frames: Option<usize>,
+    simulated_steps: usize,
+    sink: Sink,
+    resampled: Vec<u32>,
+}
+
+impl Recorder {
+    /// Empieza a grabar en `--record-dir` desde el tiempo de simulación `time`
+    pub fn start(options: &Options, width: usize, height: usize, time: f32) -> Result<Self, String> {
+        let dir = &options.record_dir;
+        fs::create_dir_all(dir).map_err(|e| format!("No se pudo crear {}: {}", dir.display(), e))?;
+        let stem = format!("grabacion_{}", timestamp(SystemTime::now()));
+
+        let (path, sink) = match options.record_format {
+            RecordFormat::Frames(format) => {
+                let path = unique_path(dir, &stem, "");
+                fs::create_dir_all(&path).map_err(|e| format!("No se pudo crear {}: {}", path.display(), e))?;
+                (path, Sink::Frames(format))
+            }
+            RecordFormat::Y4m => {
+                let path = unique_path(dir, &stem, "y4m");
+                let writer = Y4mWriter::create(&path, width, height, options.record_fps)?;
+                (path, Sink::Y4m(writer))
+            }
+        };
+
+        Ok(Recorder {
+            path,
+            width,
+            height,
+            fps: options.record_fps,
+            start_time: time,
+            frames: 0,
+            max_frames: options.record_frames,
+            simulated_steps: 0,
+            sink,
+            resampled: Vec::new(),
+        })
+    }
+
+    /// Tiempo del próximo frame y cuántos pasos fijos de simulación hay que
+    /// dar para llegar a él (como en headless, redondeando al paso más cercano)
+    pub fn advance(&mut self) -> (f32, usize) {
+        let elapsed = (self.frames + 1) as f64 / self.fps as f64;
+        let target_steps = (elapsed / SIMULATION_STEP as f64).round() as usize;
+        let steps = target_steps - self.simulated_steps;
+        self.simulated_steps = target_steps;
+        (self.start_time + elapsed as f32, steps)
+    }
+
+    /// Tiempo de simulación del último frame grabado
+    pub fn time(&self) -> f32 {
+        self.start_time + (self.frames as f64 / self.fps as f64) as f32
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Ya se grabaron los `--record-frames` pedidos
+    pub fn is_full(&self) -> bool {
+        self.max_frames.is_some_and(|max| self.frames >= max)
+    }
+
+    /// Escribe el frame ya resuelto, escalándolo si el framebuffer no tiene
+    /// la resolución de la grabación (render scale o ventana redimensionada)
+    pub fn record(&mut self, framebuffer: &Framebuffer) -> Result<(), String> {
+        let buffer = if framebuffer.width == self.width && framebuffer.height == self.height {
+            &framebuffer.buffer
+        } else {
+            framebuffer.upscale_into(&mut self.resampled, self.width, self.height);
+            &self.resampled
+        };
+
+        match &mut self.sink {
+            Sink::Frames(format) => {
+                let path = self.path.join(format!("frame_{:05}.{}", self.frames, format.extension()));
+                write_image(&path, buffer, self.width, self.height, *format)?;
+            }
+            Sink::Y4m(writer) => writer.write_frame(buffer)?,
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Cierra la grabación y devuelve dónde quedó
+    pub fn finish(self) -> Result<PathBuf, String> {
+        if let Sink::Y4m(writer) = self.sink {
+            writer.finish()?;
+        }
+        Ok(self.path)
+    }
+}
+
+/// Fracción `numerador:denominador` de la cabecera Y4M: los fps enteros tal
+/// cual y los demás en milésimas. `None` si no es finita o el numerador no
+/// queda entre 1 y `u32::MAX`, que los encoders rechazarían.
+pub fn frame_rate(fps: f32) -> Option<(u32, u32)> {
+    if !fps.is_finite() {
+        return None;
+    }
+    let (numerator, denominator) = if fps.fract() == 0.0 {
+        (fps as f64, 1)
+    } else {
+        ((fps as f64 * 1000.0).round(), 1000)
+    };
+    (1.0..=u32::MAX as f64)
+        .contains(&numerator)
+        .then_some((numerator as u32, denominator))
+}
+
+/// Video YUV4MPEG2 sin comprimir: 4:2:0 con los coeficientes BT.601 en rango
+/// limitado, que es lo que asumen ffmpeg y los encoders si no se les dice otra
+/// cosa
+pub struct Y4mWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    width: usize,
+    height: usize,
+    planes: Vec<u8>,
+}
+
+impl Y4mWriter {
+    pub fn create(path: &Path, width: usize, height: usize, fps: f32) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Error creando {}: {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+
+        let (numerator, denominator) = frame_rate(fps)
+            .ok_or_else(|| format!("{} fps no se puede guardar en Y4M", fps))?;
+        writeln!(
+            writer,
+            "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C420jpeg XCOLORRANGE=LIMITED",
+            width, height, numerator, denominator
+        )
+        .map_err(|e| format!("Error escribiendo {}: {}", path.display(), e))?;
+
+        Ok(Y4mWriter { path: path.to_path_buf(), writer, width, height, planes: Vec::new() })
+    }
+
+    /// Agrega un frame 0x00RRGGBB del tamaño del video
+    pub fn write_frame(&mut self, buffer: &[u32]) -> Result<(), String> {
+        let (width, height) = (self.width, self.height);
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        let rgb = |pixel: u32| {
+            [(pixel >> 16) & 0xFF, (pixel >> 8) & 0xFF, pixel & 0xFF].map(|c| c as f32 / 255.0)
+        };
+
+        self.planes.clear();
+        self.planes.extend(buffer.iter().map(|&pixel| {
+            let [r, g, b] = rgb(pixel);
+            to_byte(16.0 + 65.481 * r + 128.553 * g + 24.966 * b)
+        }));
+
+        // Cada muestra de croma promedia su bloque de 2×2 (recortado en los bordes)
+        let mut cb = Vec::with_capacity(chroma_width * chroma_height);
+        let mut cr = Vec::with_capacity(chroma_width * chroma_height);
+        for cy in 0..chroma_height {
+            for cx in 0..chroma_width {
+                let mut sum = [0.0; 3];
+                let mut count = 0.0;
+                for y in (cy * 2)..(cy * 2 + 2).min(height) {
+                    for x in (cx * 2)..(cx * 2 + 2).min(width) {
+                        let texel = rgb(buffer[y * width + x]);
+                        for channel in 0..3 {
+                            sum[channel] += texel[channel];
+                        }
+                        count += 1.0;
+                    }
+                }
+                let [r, g, b] = sum.map(|c| c / count);
+                cb.push(to_byte(128.0 - 37.797 * r - 74.203 * g + 112.0 * b));
+                cr.push(to_byte(128.0 + 112.0 * r - 93.786 * g - 18.214 * b));
+            }
+        }
+        self.planes.extend(cb);
+        self.planes.extend(cr);
+
+        self.writer
+            .write_all(b"FRAME\n")
+            .and_then(|_| self.writer.write_all(&self.planes))
+            .map_err(|e| format!("Error escribiendo {}: {}", self.path.display(), e))
+    }
+
+    pub fn finish(mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|e| format!("Error escribiendo {}: {}", self.path.display(), e))
+    }
+}
+
+fn to_byte(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output_dir(name: &str) -> PathBuf {
+        let dir = Path::new("target/recording").join(name);
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn frame_rates_are_fractions_encoders_accept() {
+        assert_eq!(frame_rate(30.0), Some((30, 1)));
+        assert_eq!(frame_rate(29.97), Some((29970, 1000)));
+        assert_eq!(frame_rate(0.5), Some((500, 1000)));
+        for fps in [0.0, -24.0, 0.0001, 1e10, f32::NAN, f32::INFINITY] {
+            assert_eq!(frame_rate(fps), None, "{}", fps);
+        }
+    }
+
+    /// Cabecera y luego, por frame, `FRAME` con los planos Y, Cb y Cr
+    #[test]
+    fn y4m_writer_lays_out_header_and_frames() {
+        let dir = output_dir("y4m");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("video.y4m");
+
+        // Un bloque de 2×2 rojo y otro blanco
+        let (red, white) = (0xFF0000, 0xFFFFFF);
+        let frame = [red, red, white, white, red, red, white, white];
+        let mut writer = Y4mWriter::create(&path, 4, 2, 29.97).unwrap();
+        writer.write_frame(&frame).unwrap();
+        writer.write_frame(&frame).unwrap();
+        writer.finish().unwrap();
+
+        let header = b"YUV4MPEG2 W4 H2 F29970:1000 Ip A1:1 C420jpeg XCOLORRANGE=LIMITED\n";
+        let planes = [81, 81, 235, 235, 81, 81, 235, 235, 90, 128, 240, 128];
+        let mut expected = header.to_vec();
+        for _ in 0..2 {
+            expected.extend_from_slice(b"FRAME\n");
+            expected.extend_from_slice(&planes);
+        }
+        assert_eq!(fs::read(&path).unwrap(), expected);
+    }
+
+    /// A 24 fps cada frame son 2.5 pasos de 1/60: se alternan 2 y 3 sin
+    /// acumular error, y el tiempo no depende de cuánto tarde el render
+    #[test]
+    fn recorder_advances_a_fixed_number_of_steps_per_frame() {
+        let options = Options {
+            record_dir: output_dir("frames"),
+            record_format: RecordFormat::Frames(ImageFormat::Ppm),
+            record_fps: 24.0,
+            record_frames: Some(4),
+            ..Options::default()
+        };
+        let mut recorder = Recorder::start(&options, 4, 2, 10.0).unwrap();
+        let framebuffer = Framebuffer::new(2, 1);
+
+        let mut steps = Vec::new();
+        while !recorder.is_full() {
+            let (time, frame_steps) = recorder.advance();
+            assert!((time - (10.0 + (recorder.frames() + 1) as f32 / 24.0)).abs() < 1e-5);
+            steps.push(frame_steps);
+            recorder.record(&framebuffer).unwrap();
+        }
+        assert_eq!(steps, [2, 3, 2, 3]);
+        assert_eq!(recorder.frames(), 4);
+
+        let path = recorder.finish().unwrap();
+        let mut files: Vec<_> = fs::read_dir(&path).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        files.sort();
+        assert_eq!(files, ["frame_00000.ppm", "frame_00001.ppm", "frame_00002.ppm", "frame_00003.ppm"]);
+    }
+}
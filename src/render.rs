@@ -0,0 +1,1010 @@
+use crate::framebuffer::{BlendMode, Framebuffer};
+use crate::triangle::{RasterSample, Triangle};
+use crate::line::Line;
+use crate::point::{Point, PointShape, PointSize};
+use crate::frustum::{BoundingSphere, Frustum};
+use crate::vertex::Vertex;
+use crate::shaders::{vertex_shader, fragment_shader, Uniforms};
+use crate::clipping::{clip_line, clip_triangle, project_to_screen};
+use crate::tile::{GBuffer, Tile, TriangleId, TILE_SIZE};
+use crate::antialiasing::{sample_pattern, MAX_SAMPLES};
+use crate::color::HdrColor;
+use crate::depth::DepthMode;
+use crate::debug_view::{debug_shader, overdraw_color, wireframe_overlay, DebugView};
+
+use std::ops::AddAssign;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Qué caras descartar antes de rasterizar
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CullMode {
+    None,
+    Back,
+    /// Para una malla cerrada vista desde adentro sin invertir su orden de
+    /// vértices; el skybox ya viene invertido y no lo necesita
+    #[cfg_attr(not(test), allow(dead_code))]
+    Front,
+}
+
+/// Orden de los vértices (visto en pantalla) que define la cara frontal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Winding {
+    CounterClockwise,
+    Clockwise,
+}
+
+/// Estado fijo de una llamada de dibujo
+#[derive(Clone, Copy, Debug)]
+pub struct DrawState {
+    pub cull_mode: CullMode,
+    pub front_face: Winding,
+    pub blend_mode: BlendMode,
+}
+
+impl DrawState {
+    /// Geometría abierta que debe verse por ambos lados (anillos, órbitas)
+    pub fn double_sided() -> Self {
+        DrawState {
+            cull_mode: CullMode::None,
+            ..DrawState::default()
+        }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_cull_mode(mut self, cull_mode: CullMode) -> Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+
+    pub fn with_front_face(mut self, front_face: Winding) -> Self {
+        self.front_face = front_face;
+        self
+    }
+
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    /// El mismo estado con la cara frontal invertida, para mallas dibujadas
+    /// con una matriz de modelo espejada
+    pub fn mirrored(self) -> Self {
+        let front_face = match self.front_face {
+            Winding::CounterClockwise => Winding::Clockwise,
+            Winding::Clockwise => Winding::CounterClockwise,
+        };
+        self.with_front_face(front_face)
+    }
+
+    /// Decide si un polígono se descarta según su área con signo en pantalla
+    fn culls(&self, signed_area: f32) -> bool {
+        if self.cull_mode == CullMode::None {
+            return false;
+        }
+        if signed_area == 0.0 {
+            return true;
+        }
+
+        let is_front = match self.front_face {
+            Winding::CounterClockwise => signed_area > 0.0,
+            Winding::Clockwise => signed_area < 0.0,
+        };
+
+        match self.cull_mode {
+            CullMode::Back => !is_front,
+            CullMode::Front => is_front,
+            CullMode::None => false,
+        }
+    }
+}
+
+impl Default for DrawState {
+    /// Mallas cerradas con caras frontales antihorarias (convención OBJ)
+    fn default() -> Self {
+        DrawState {
+            cull_mode: CullMode::Back,
+            front_face: Winding::CounterClockwise,
+            blend_mode: BlendMode::Opaque,
+        }
+    }
+}
+
+/// Doble del área con signo del polígono proyectado. Positiva si es antihorario
+/// visto en pantalla (el viewport invierte Y, por eso el signo negativo).
+fn screen_signed_area(polygon: &[Vertex]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..polygon.len() {
+        let a = polygon[i].transformed_position;
+        let b = polygon[(i + 1) % polygon.len()].transformed_position;
+        area += a.x * b.y - b.x * a.y;
+    }
+    -area
+}
+
+/// Contadores del último `flush`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RenderStats {
+    pub triangles: usize,
+    pub lines: usize,
+    pub points: usize,
+    /// Polígonos descartados por back/front-face culling
+    pub triangles_culled: usize,
+    /// Objetos (cuerpos, anillos, tramos de órbita) dentro y fuera del frustum
+    pub objects_drawn: usize,
+    pub objects_frustum_culled: usize,
+    /// Ejecuciones de vertex shader (una por vértice único de cada llamada)
+    pub vertex_shader_invocations: usize,
+    /// Píxeles cubiertos por algún triángulo
+    pub fragments: usize,
+    /// Fragmentos descartados por el test de profundidad antes de sombrear
+    pub early_z_rejected: usize,
+    pub shader_invocations: usize,
+}
+
+impl RenderStats {
+    /// Ejecuciones de fragment shader evitadas respecto a sombrear todo
+    pub fn shader_invocations_saved(&self) -> usize {
+        self.fragments.saturating_sub(self.shader_invocations)
+    }
+
+    pub fn saved_percentage(&self) -> f32 {
+        if self.fragments == 0 {
+            0.0
+        } else {
+            self.shader_invocations_saved() as f32 / self.fragments as f32 * 100.0
+        }
+    }
+}
+
+impl AddAssign for RenderStats {
+    fn add_assign(&mut self, other: RenderStats) {
+        self.triangles += other.triangles;
+        self.lines += other.lines;
+        self.points += other.points;
+        self.triangles_culled += other.triangles_culled;
+        self.objects_drawn += other.objects_drawn;
+        self.objects_frustum_culled += other.objects_frustum_culled;
+        self.vertex_shader_invocations += other.vertex_shader_invocations;
+        self.fragments += other.fragments;
+        self.early_z_rejected += other.early_z_rejected;
+        self.shader_invocations += other.shader_invocations;
+    }
+}
+
+/// Geometría de una llamada de dibujo, ya recortada y en pantalla
+enum Primitives {
+    Triangles(Vec<Triangle>),
+    Lines(Vec<Line>),
+    Points(Vec<Point>),
+}
+
+/// Un cuerpo ya transformado y recortado, listo para rasterizar.
+/// Los índices de los bins apuntan a la lista de primitivas de la llamada.
+struct DrawCall {
+    uniforms: Uniforms,
+    shader_type: String,
+    blend_mode: BlendMode,
+    primitives: Primitives,
+}
+
+impl DrawCall {
+    /// Líneas y puntos siempre se mezclan (su borde tiene cobertura parcial)
+    fn is_transparent(&self) -> bool {
+        self.blend_mode.is_transparent() || !matches!(self.primitives, Primitives::Triangles(_))
+    }
+
+    fn primitive_count(&self) -> usize {
+        match &self.primitives {
+            Primitives::Triangles(triangles) => triangles.len(),
+            Primitives::Lines(lines) => lines.len(),
+            Primitives::Points(points) => points.len(),
+        }
+    }
+
+    fn bounding_box(&self, index: usize) -> (i32, i32, i32, i32) {
+        match &self.primitives {
+            Primitives::Triangles(triangles) => triangles[index].bounding_box(),
+            Primitives::Lines(lines) => lines[index].bounding_box(),
+            Primitives::Points(points) => points[index].bounding_box(),
+        }
+    }
+
+    fn view_depth(&self, index: usize) -> f32 {
+        match &self.primitives {
+            Primitives::Triangles(triangles) => triangles[index].view_depth(),
+            Primitives::Lines(lines) => lines[index].view_depth(),
+            Primitives::Points(points) => points[index].view_depth(),
+        }
+    }
+
+    /// Cantidad de primitivas de un tipo, para las estadísticas
+    fn count_of(&self, kind: fn(&Primitives) -> bool) -> usize {
+        if kind(&self.primitives) {
+            self.primitive_count()
+        } else {
+            0
+        }
+    }
+}
+
+/// Las primitivas con cobertura propia no tienen modo opaco: se usa mezcla alfa
+fn coverage_blend_mode(state: &DrawState) -> BlendMode {
+    match state.blend_mode {
+        BlendMode::Opaque => BlendMode::Alpha,
+        mode => mode,
+    }
+}
+
+/// Rasterizador por tiles. Las llamadas a `draw` solo transforman y recortan
+/// geometría; `flush` reparte los tiles de la pantalla entre `threads` hilos.
+/// Dentro de cada tile los triángulos se procesan en el orden de envío, por lo
+/// que el resultado es idéntico con cualquier cantidad de hilos.
+///
+/// Las llamadas con `BlendMode` distinto de `Opaque` forman una pasada
+/// transparente: se dibujan después de todo lo opaco, con sus triángulos
+/// ordenados de atrás hacia adelante, probando profundidad sin escribirla.
+///
+/// Con `early_z` activo todos los triángulos opacos del frame se rasterizan
+/// primero a un G-buffer por tile (solo profundidad, triángulo ganador y pesos)
+/// y después se sombrea una vez por píxel visible, aunque lo que lo tapa se
+/// haya enviado más tarde; sin él se sombrea cada fragmento antes del test de
+/// profundidad.
+///
+/// Con `samples > 1` (MSAA) la cobertura y la profundidad se evalúan en varias
+/// posiciones por píxel, pero el shader corre una vez por triángulo y píxel.
+///
+/// `pixel_scale` es cuántos píxeles del framebuffer equivalen a un píxel de la
+/// ventana (mayor que 1 con SSAA, menor con `render_scale` < 1); los anchos en
+/// píxeles se multiplican por él.
+pub struct Renderer {
+    pub threads: usize,
+    pub early_z: bool,
+    pub samples: usize,
+    /// Qué profundidad se guarda en el z-buffer
+    pub depth_mode: DepthMode,
+    /// Vista de depuración en lugar del sombreado normal
+    pub debug_view: DebugView,
+    pub pixel_scale: f32,
+    /// Fracción del tamaño de la ventana a la que se renderiza
+    pub render_scale: f32,
+    pub stats: RenderStats,
+    draws: Vec<DrawCall>,
+    culled: usize,
+    vertices_shaded: usize,
+    objects_drawn: usize,
+    objects_frustum_culled: usize,
+    /// `w` mínima y máxima de los triángulos del frame, para la vista de profundidad
+    depth_range: (f32, f32),
+}
+
+impl Renderer {
+    pub fn new(threads: usize) -> Self {
+        Renderer {
+            threads: threads.max(1),
+            early_z: true,
+            samples: 1,
+            depth_mode: DepthMode::Standard,
+            debug_view: DebugView::Off,
+            pixel_scale: 1.0,
+            render_scale: 1.0,
+            stats: RenderStats::default(),
+            draws: Vec::new(),
+            culled: 0,
+            vertices_shaded: 0,
+            objects_drawn: 0,
+            objects_frustum_culled: 0,
+            depth_range: (0.0, 1.0),
+        }
+    }
+
+    /// Cantidad de hilos disponibles en la máquina
+    pub fn default_threads() -> usize {
+        thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    }
+
+    /// Prueba la esfera envolvente de un objeto contra el frustum y lo cuenta
+    /// como dibujado o descartado. Si devuelve falso no hace falta enviarlo.
+    pub fn is_visible(&mut self, frustum: &Frustum, bounds: &BoundingSphere) -> bool {
+        let visible = frustum.intersects_sphere(bounds);
+        if visible {
+            self.objects_drawn += 1;
+        } else {
+            self.objects_frustum_culled += 1;
+        }
+        visible
+    }
+
+    /// Dibuja una lista de triángulos sin indexar (tres vértices seguidos por
+    /// triángulo)
+    pub fn draw(
+        &mut self,
+        uniforms: &Uniforms,
+        vertex_array: &[Vertex],
+        shader_type: &str,
+        state: &DrawState,
+    ) {
+        let indices: Vec<u32> = (0..vertex_array.len() as u32).collect();
+        self.draw_indexed(uniforms, vertex_array, &indices, shader_type, state);
+    }
+
+    /// Dibuja una malla indexada: cada vértice único pasa una sola vez por el
+    /// vertex shader y los triángulos (tres índices cada uno) comparten el
+    /// resultado ya transformado
+    pub fn draw_indexed(
+        &mut self,
+        uniforms: &Uniforms,
+        vertex_array: &[Vertex],
+        indices: &[u32],
+        shader_type: &str,
+        state: &DrawState,
+    ) {
+        let transformed_vertices: Vec<Vertex> = vertex_array
+            .iter()
+            .map(|vertex| vertex_shader(vertex, uniforms))
+            .collect();
+        self.vertices_shaded += transformed_vertices.len();
+
+        // Una matriz de modelo espejada invierte el orden de los vértices en pantalla
+        let state = if uniforms.model_matrix.fixed_view::<3, 3>(0, 0).determinant() < 0.0 {
+            state.mirrored()
+        } else {
+            *state
+        };
+
+        let mut triangles = Vec::new();
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| &transformed_vertices[triangle[i] as usize]);
+
+            // Recortar en clip space antes de la división de perspectiva
+            let mut polygon = clip_triangle(a, b, c);
+            for vertex in &mut polygon {
+                project_to_screen(vertex, &uniforms.viewport_matrix, self.depth_mode);
+            }
+
+            // El recorte conserva la orientación: se decide una vez por polígono
+            if polygon.len() >= 3 && state.culls(screen_signed_area(&polygon)) {
+                self.culled += 1;
+                continue;
+            }
+
+            // Triangulación en abanico del polígono recortado
+            for i in 1..polygon.len().saturating_sub(1) {
+                triangles.push(Triangle::new_from_vertices(
+                    polygon[0].clone(),
+                    polygon[i].clone(),
+                    polygon[i + 1].clone(),
+                ));
+            }
+        }
+
+        self.draws.push(DrawCall {
+            uniforms: uniforms.clone(),
+            shader_type: shader_type.to_string(),
+            blend_mode: state.blend_mode,
+            primitives: Primitives::Triangles(triangles),
+        });
+    }
+
+    /// Dibuja una lista de segmentos (pares de vértices) de `width` píxeles de
+    /// ancho, con el color de cada vértice y bordes antialiasados. Las líneas
+    /// prueban profundidad sin escribirla y se mezclan en la pasada transparente
+    /// (con `BlendMode::Opaque` se usa mezcla alfa).
+    pub fn draw_lines(
+        &mut self,
+        uniforms: &Uniforms,
+        vertex_array: &[Vertex],
+        width: f32,
+        state: &DrawState,
+    ) {
+        let width = width * self.pixel_scale;
+        let mut lines = Vec::with_capacity(vertex_array.len() / 2);
+
+        self.vertices_shaded += vertex_array.len() / 2 * 2;
+        for pair in vertex_array.chunks_exact(2) {
+            let v1 = vertex_shader(&pair[0], uniforms);
+            let v2 = vertex_shader(&pair[1], uniforms);
+
+            if let Some((mut start, mut end)) = clip_line(&v1, &v2) {
+                project_to_screen(&mut start, &uniforms.viewport_matrix, self.depth_mode);
+                project_to_screen(&mut end, &uniforms.viewport_matrix, self.depth_mode);
+                lines.push(Line::new(start, end, width));
+            }
+        }
+
+        if lines.is_empty() {
+            return;
+        }
+
+        self.draws.push(DrawCall {
+            uniforms: uniforms.clone(),
+            shader_type: String::new(),
+            blend_mode: coverage_blend_mode(state),
+            primitives: Primitives::Lines(lines),
+        });
+    }
+
+    /// Dibuja cada vértice como un punto mirando a la cámara (disco o
+    /// cuadrado) con el color del vértice. Prueba profundidad sin escribirla y
+    /// se mezcla en la pasada transparente; con `BlendMode::Additive` sirve para
+    /// estrellas, partículas y cuerpos lejanos de menos de un píxel.
+    pub fn draw_points(
+        &mut self,
+        uniforms: &Uniforms,
+        vertex_array: &[Vertex],
+        size: PointSize,
+        shape: PointShape,
+        state: &DrawState,
+    ) {
+        // Escala de radio en mundo a píxeles a distancia 1 (foco vertical × medio alto)
+        let focal = uniforms.projection_matrix[(1, 1)] * uniforms.viewport_matrix[(1, 1)].abs();
+        let mut points = Vec::with_capacity(vertex_array.len());
+
+        self.vertices_shaded += vertex_array.len();
+        for vertex in vertex_array {
+            let mut center = vertex_shader(vertex, uniforms);
+            let clip = center.clip_position;
+
+            // Solo se descartan por profundidad; los que salen por los lados
+            // pueden asomar su borde y los recorta el binning
+            if clip.w <= 0.0 || clip.z < -clip.w || clip.z > clip.w {
+                continue;
+            }
+
+            let radius = match size {
+                PointSize::Pixels(pixels) => pixels * 0.5 * self.pixel_scale,
+                PointSize::World(radius) => radius * focal / clip.w,
+            };
+
+            project_to_screen(&mut center, &uniforms.viewport_matrix, self.depth_mode);
+            points.push(Point::new(center, radius, shape));
+        }
+
+        if points.is_empty() {
+            return;
+        }
+
+        self.draws.push(DrawCall {
+            uniforms: uniforms.clone(),
+            shader_type: String::new(),
+            blend_mode: coverage_blend_mode(state),
+            primitives: Primitives::Points(points),
+        });
+    }
+
+    /// Rasteriza y sombrea todo lo enviado desde el último `flush`
+    pub fn flush(&mut self, framebuffer: &mut Framebuffer) {
+        let tiles_x = framebuffer.width.div_ceil(TILE_SIZE);
+        let tiles_y = framebuffer.height.div_ceil(TILE_SIZE);
+        let bins = self.bin_primitives(tiles_x, tiles_y);
+
+        let next_tile = AtomicUsize::new(0);
+        let finished = Mutex::new(Vec::with_capacity(bins.len()));
+        let total_stats = Mutex::new(RenderStats {
+            triangles: self.draws.iter().map(|draw| draw.count_of(|p| matches!(p, Primitives::Triangles(_)))).sum(),
+            lines: self.draws.iter().map(|draw| draw.count_of(|p| matches!(p, Primitives::Lines(_)))).sum(),
+            points: self.draws.iter().map(|draw| draw.count_of(|p| matches!(p, Primitives::Points(_)))).sum(),
+            triangles_culled: self.culled,
+            vertex_shader_invocations: self.vertices_shaded,
+            objects_drawn: self.objects_drawn,
+            objects_frustum_culled: self.objects_frustum_culled,
+            ..RenderStats::default()
+        });
+
+        let samples = self.samples.clamp(1, MAX_SAMPLES);
+        if self.debug_view == DebugView::Depth {
+            self.depth_range = self.triangle_depth_range();
+        }
+
+        let work = || {
+            let mut gbuffer = GBuffer::new(samples);
+            let mut stats = RenderStats::default();
+
+            loop {
+                let index = next_tile.fetch_add(1, Ordering::Relaxed);
+                if index >= bins.len() {
+                    break;
+                }
+                if bins[index].is_empty() {
+                    continue;
+                }
+
+                let x0 = (index % tiles_x) * TILE_SIZE;
+                let y0 = (index / tiles_x) * TILE_SIZE;
+                let mut tile = Tile::load(framebuffer, x0, y0, samples);
+                self.rasterize_tile(&mut tile, &bins[index], &mut gbuffer, &mut stats);
+                finished.lock().unwrap().push(tile);
+            }
+
+            *total_stats.lock().unwrap() += stats;
+        };
+
+        if self.threads == 1 {
+            work();
+        } else {
+            thread::scope(|scope| {
+                for _ in 0..self.threads {
+                    scope.spawn(work);
+                }
+            });
+        }
+
+        for tile in finished.into_inner().unwrap() {
+            tile.store(framebuffer);
+        }
+
+        self.stats = total_stats.into_inner().unwrap();
+        self.draws.clear();
+        self.culled = 0;
+        self.vertices_shaded = 0;
+        self.objects_drawn = 0;
+        self.objects_frustum_culled = 0;
+    }
+
+    /// Orden de rasterización: primero lo opaco en orden de envío, luego los
+    /// triángulos y líneas transparentes de todas las llamadas, del más lejano
+    /// al más cercano
+    fn submission_order(&self) -> Vec<(usize, usize)> {
+        let mut opaque = Vec::new();
+        let mut transparent = Vec::new();
+
+        for (draw_index, draw) in self.draws.iter().enumerate() {
+            for index in 0..draw.primitive_count() {
+                if draw.is_transparent() {
+                    transparent.push((draw.view_depth(index), draw_index, index));
+                } else {
+                    opaque.push((draw_index, index));
+                }
+            }
+        }
+
+        // Orden estable: a igual distancia se respeta el orden de envío
+        transparent.sort_by(|a, b| b.0.total_cmp(&a.0));
+        opaque.extend(transparent.into_iter().map(|(_, draw, index)| (draw, index)));
+        opaque
+    }
+
+    /// Asigna cada primitiva a los tiles que toca su caja envolvente
+    fn bin_primitives(&self, tiles_x: usize, tiles_y: usize) -> Vec<Vec<(usize, usize)>> {
+        let mut bins = vec![Vec::new(); tiles_x * tiles_y];
+        let max_x = (tiles_x * TILE_SIZE) as i32 - 1;
+        let max_y = (tiles_y * TILE_SIZE) as i32 - 1;
+
+        for (draw_index, primitive_index) in self.submission_order() {
+            let (min_x, min_y, box_max_x, box_max_y) = self.draws[draw_index].bounding_box(primitive_index);
+            if box_max_x < 0 || box_max_y < 0 || min_x > max_x || min_y > max_y {
+                continue;
+            }
+
+            let first_tx = min_x.max(0) as usize / TILE_SIZE;
+            let first_ty = min_y.max(0) as usize / TILE_SIZE;
+            let last_tx = box_max_x.min(max_x) as usize / TILE_SIZE;
+            let last_ty = box_max_y.min(max_y) as usize / TILE_SIZE;
+
+            for ty in first_ty..=last_ty {
+                for tx in first_tx..=last_tx {
+                    bins[ty * tiles_x + tx].push((draw_index, primitive_index));
+                }
+            }
+        }
+
+        bins
+    }
+
+    fn rasterize_tile(
+        &self,
+        tile: &mut Tile,
+        bin: &[(usize, usize)],
+        gbuffer: &mut GBuffer,
+        stats: &mut RenderStats,
+    ) {
+        let pattern = sample_pattern(tile.samples);
+
+        // Los triángulos opacos van primero en el bin (ver `submission_order`)
+        let opaque_count = bin.partition_point(|&(draw_index, _)| !self.draws[draw_index].is_transparent());
+        let (opaque, transparent) = bin.split_at(opaque_count);
+
+        if self.early_z {
+            self.shade_deferred(tile, opaque, pattern, gbuffer, stats);
+        } else {
+            for run in opaque.chunk_by(|a, b| a.0 == b.0) {
+                let draw = &self.draws[run[0].0];
+                if let Primitives::Triangles(triangles) = &draw.primitives {
+                    self.shade_forward(tile, draw, triangles, run.iter().map(|&(_, index)| index), pattern, stats);
+                }
+            }
+        }
+
+        // El resto está agrupado por llamada de dibujo: se procesa una a la vez
+        for run in transparent.chunk_by(|a, b| a.0 == b.0) {
+            let draw = &self.draws[run[0].0];
+            let indices = run.iter().map(|&(_, index)| index);
+
+            match &draw.primitives {
+                Primitives::Lines(lines) => {
+                    Self::draw_line_run(tile, draw.blend_mode, lines, indices);
+                }
+                Primitives::Points(points) => {
+                    Self::draw_point_run(tile, draw.blend_mode, points, indices);
+                }
+                Primitives::Triangles(triangles) => {
+                    self.shade_blended(tile, draw, triangles, indices, pattern, stats);
+                }
+            }
+        }
+
+        if self.debug_view == DebugView::Overdraw {
+            tile.show_overdraw(overdraw_color);
+        }
+    }
+
+    /// Color de una muestra visible: el `fragment_shader` del cuerpo o, con
+    /// una vista de depuración activa, lo que esta pida en su lugar
+    fn shade(&self, triangle: &Triangle, sample: &RasterSample, draw: &DrawCall) -> HdrColor {
+        let fragment = triangle.fragment(sample);
+        if let Some(color) = debug_shader(&fragment, &draw.uniforms, self.debug_view, self.depth_range) {
+            return color;
+        }
+
+        let color = fragment_shader(&fragment, &draw.uniforms, &draw.shader_type);
+        if self.debug_view == DebugView::Wireframe {
+            wireframe_overlay(color, triangle.edge_distance(sample), self.pixel_scale)
+        } else {
+            color
+        }
+    }
+
+    /// Rango de la `w` de clip (profundidad lineal de vista) de todos los
+    /// vértices de triángulos enviados en el frame
+    fn triangle_depth_range(&self) -> (f32, f32) {
+        let mut range = (f32::INFINITY, f32::NEG_INFINITY);
+        for draw in &self.draws {
+            if let Primitives::Triangles(triangles) = &draw.primitives {
+                for triangle in triangles {
+                    for vertex in [&triangle.v1, &triangle.v2, &triangle.v3] {
+                        range.0 = range.0.min(vertex.clip_position.w);
+                        range.1 = range.1.max(vertex.clip_position.w);
+                    }
+                }
+            }
+        }
+
+        if range.0 <= range.1 { range } else { (0.0, 1.0) }
+    }
+
+    /// Sombrea cada fragmento (una vez por píxel cubierto, en su primera
+    /// muestra) y luego hace el test de profundidad de cada muestra
+    fn shade_forward(
+        &self,
+        tile: &mut Tile,
+        draw: &DrawCall,
+        triangles: &[Triangle],
+        indices: impl Iterator<Item = usize>,
+        pattern: &[(f32, f32)],
+        stats: &mut RenderStats,
+    ) {
+        let rect = tile.rect();
+
+        for triangle_index in indices {
+            let triangle = &triangles[triangle_index];
+            let mut shaded: Option<(i32, i32, HdrColor)> = None;
+
+            triangle.rasterize(rect, pattern, |sample| {
+                let color = match shaded {
+                    Some((x, y, color)) if x == sample.x && y == sample.y => color,
+                    _ => {
+                        let color = self.shade(triangle, &sample, draw);
+                        shaded = Some((sample.x, sample.y, color));
+                        stats.fragments += 1;
+                        stats.shader_invocations += 1;
+                        tile.count_fragment(sample.x, sample.y);
+                        color
+                    }
+                };
+
+                let pixel = tile.index(sample.x as usize, sample.y as usize);
+                let index = tile.sample_index(pixel, sample.sample);
+                if sample.depth < tile.depth[index] {
+                    tile.color[index] = color;
+                    tile.depth[index] = sample.depth;
+                }
+            });
+        }
+    }
+
+    /// Líneas: la cobertura analítica escala el alfa del color del vértice y
+    /// se mezcla en cada muestra del píxel que pase el test de profundidad
+    fn draw_line_run(
+        tile: &mut Tile,
+        blend_mode: BlendMode,
+        lines: &[Line],
+        indices: impl Iterator<Item = usize>,
+    ) {
+        let rect = tile.rect();
+
+        for line_index in indices {
+            lines[line_index].rasterize(rect, |sample| {
+                tile.count_fragment(sample.x, sample.y);
+                Self::blend_coverage(tile, blend_mode, sample.x, sample.y, sample.depth, sample.color, sample.coverage);
+            });
+        }
+    }
+
+    /// Puntos: igual que las líneas, con la cobertura del disco o cuadrado
+    fn draw_point_run(
+        tile: &mut Tile,
+        blend_mode: BlendMode,
+        points: &[Point],
+        indices: impl Iterator<Item = usize>,
+    ) {
+        let rect = tile.rect();
+
+        for point_index in indices {
+            points[point_index].rasterize(rect, |sample| {
+                tile.count_fragment(sample.x, sample.y);
+                Self::blend_coverage(tile, blend_mode, sample.x, sample.y, sample.depth, sample.color, sample.coverage);
+            });
+        }
+    }
+
+    /// Mezcla un color con cobertura parcial en las muestras del píxel que
+    /// pasen el test de profundidad (sin escribirla)
+    fn blend_coverage(
+        tile: &mut Tile,
+        blend_mode: BlendMode,
+        x: i32,
+        y: i32,
+        depth: f32,
+        mut color: HdrColor,
+        coverage: f32,
+    ) {
+        color.a *= coverage;
+
+        let pixel = tile.index(x as usize, y as usize);
+        for s in 0..tile.samples {
+            let index = tile.sample_index(pixel, s);
+            if depth < tile.depth[index] {
+                tile.color[index] = blend_mode.blend(color, tile.color[index]);
+            }
+        }
+    }
+
+    /// Pasada transparente: cada muestra que pasa la profundidad se mezcla
+    /// con el color del tile según el modo de la llamada. El shader corre la
+    /// primera vez que una muestra del píxel pasa el test.
+    fn shade_blended(
+        &self,
+        tile: &mut Tile,
+        draw: &DrawCall,
+        triangles: &[Triangle],
+        indices: impl Iterator<Item = usize>,
+        pattern: &[(f32, f32)],
+        stats: &mut RenderStats,
+    ) {
+        let rect = tile.rect();
+
+        for triangle_index in indices {
+            let triangle = &triangles[triangle_index];
+            let mut last_pixel = None;
+            let mut shaded: Option<(i32, i32, HdrColor)> = None;
+
+            triangle.rasterize(rect, pattern, |sample| {
+                let pixel = tile.index(sample.x as usize, sample.y as usize);
+                if last_pixel != Some((sample.x, sample.y)) {
+                    last_pixel = Some((sample.x, sample.y));
+                    stats.fragments += 1;
+                    tile.overdraw[pixel] += 1;
+                }
+
+                let index = tile.sample_index(pixel, sample.sample);
+                if sample.depth >= tile.depth[index] {
+                    return;
+                }
+
+                let color = match shaded {
+                    Some((x, y, color)) if x == sample.x && y == sample.y => color,
+                    _ => {
+                        let color = self.shade(triangle, &sample, draw);
+                        shaded = Some((sample.x, sample.y, color));
+                        stats.shader_invocations += 1;
+                        color
+                    }
+                };
+                tile.color[index] = draw.blend_mode.blend(color, tile.color[index]);
+            });
+        }
+    }
+
+    /// Pasada opaca con early-z: primero resuelve la visibilidad de todos los
+    /// triángulos opacos del tile en el G-buffer, de cualquier llamada de dibujo,
+    /// y recién después sombrea una vez por triángulo visible en cada píxel. Así
+    /// no se sombrea lo que tapa algo dibujado más tarde (el sol detrás de un
+    /// planeta). Como el test es estricto (`<`) y se respeta el orden de envío,
+    /// gana el mismo fragmento que en el camino forward.
+    fn shade_deferred(
+        &self,
+        tile: &mut Tile,
+        opaque: &[TriangleId],
+        pattern: &[(f32, f32)],
+        gbuffer: &mut GBuffer,
+        stats: &mut RenderStats,
+    ) {
+        let rect = tile.rect();
+
+        for &id in opaque {
+            let mut last_pixel = None;
+
+            self.triangle(id).1.rasterize(rect, pattern, |sample| {
+                let pixel = tile.index(sample.x as usize, sample.y as usize);
+                if last_pixel != Some((sample.x, sample.y)) {
+                    last_pixel = Some((sample.x, sample.y));
+                    stats.fragments += 1;
+                    tile.overdraw[pixel] += 1;
+                }
+
+                let index = tile.sample_index(pixel, sample.sample);
+                if sample.depth < tile.depth[index] {
+                    tile.depth[index] = sample.depth;
+                    gbuffer.write(pixel, id, sample);
+                } else {
+                    stats.early_z_rejected += 1;
+                }
+            });
+        }
+
+        gbuffer.drain(|pixel, entries| {
+            // Colores ya calculados en este píxel, por triángulo
+            let mut shaded = [((usize::MAX, usize::MAX), HdrColor::black()); MAX_SAMPLES];
+            let mut shaded_count = 0;
+
+            for (sample_index, entry) in entries.iter().enumerate() {
+                let Some((id, sample)) = entry else {
+                    continue;
+                };
+
+                let color = match shaded[..shaded_count].iter().find(|(shaded_id, _)| shaded_id == id) {
+                    Some(&(_, color)) => color,
+                    None => {
+                        let (draw, triangle) = self.triangle(*id);
+                        let color = self.shade(triangle, sample, draw);
+                        shaded[shaded_count] = (*id, color);
+                        shaded_count += 1;
+                        stats.shader_invocations += 1;
+                        color
+                    }
+                };
+
+                let index = tile.sample_index(pixel, sample_index);
+                tile.color[index] = color;
+            }
+        });
+    }
+
+    /// Llamada de dibujo y triángulo de un elemento opaco del bin
+    fn triangle(&self, (draw_index, triangle_index): TriangleId) -> (&DrawCall, &Triangle) {
+        let draw = &self.draws[draw_index];
+        match &draw.primitives {
+            Primitives::Triangles(triangles) => (draw, &triangles[triangle_index]),
+            _ => unreachable!("solo los triángulos pueden ser opacos"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::create_viewport_matrix;
+    use crate::shadows::ShadowCasters;
+    use nalgebra_glm::{Mat4, Vec2, Vec3};
+    use std::sync::Arc;
+
+    const SIZE: usize = 32;
+
+    fn uniforms() -> Uniforms {
+        Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix: Mat4::identity(),
+            projection_matrix: Mat4::identity(),
+            viewport_matrix: create_viewport_matrix(SIZE as f32, SIZE as f32),
+            time: 0.0,
+            camera_position: Vec3::zeros(),
+            lights: Arc::new(Vec::new()),
+            shadows: Arc::new(ShadowCasters::new()),
+            surface_map: None,
+            baked_layers: None,
+        }
+    }
+
+    /// Cuadrado de lado `2 * half` en clip space (proyección identidad) a profundidad `z`
+    fn quad(half: f32, z: f32) -> Vec<Vertex> {
+        let corner = |x: f32, y: f32| Vertex::new(Vec3::new(x * half, y * half, z), Vec3::new(0.0, 0.0, 1.0), Vec2::zeros());
+        vec![
+            corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0),
+            corner(-1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0),
+        ]
+    }
+
+    /// Un fondo que cubre toda la pantalla y, enviado después, un cuadrado más
+    /// cerca que tapa el centro; devuelve las estadísticas y los píxeles visibles
+    fn render_occluded(early_z: bool) -> (RenderStats, usize) {
+        let mut renderer = Renderer::new(1);
+        renderer.early_z = early_z;
+        let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+        framebuffer.clear();
+
+        let uniforms = uniforms();
+        renderer.draw(&uniforms, &quad(1.0, 0.5), "test", &DrawState::double_sided());
+        renderer.draw(&uniforms, &quad(0.5, -0.5), "test", &DrawState::double_sided());
+        renderer.flush(&mut framebuffer);
+
+        let visible = framebuffer.zbuffer.iter().filter(|&&depth| depth < 1000.0).count();
+        (renderer.stats, visible)
+    }
+
+    #[test]
+    fn early_z_shades_each_visible_pixel_once_across_draws() {
+        let (stats, visible) = render_occluded(true);
+
+        assert_eq!(visible, SIZE * SIZE);
+        // El fondo detrás del cuadrado se rasteriza pero no se sombrea
+        assert!(stats.fragments >= SIZE * SIZE + (SIZE / 2) * (SIZE / 2));
+        assert_eq!(stats.shader_invocations, visible);
+    }
+
+    /// Píxeles que cubre `vertices` dibujado con `model_matrix` y `state`
+    fn covered_pixels(vertices: &[Vertex], model_matrix: Mat4, state: &DrawState) -> usize {
+        let mut renderer = Renderer::new(1);
+        let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+        framebuffer.clear();
+
+        let uniforms = Uniforms { model_matrix, ..uniforms() };
+        renderer.draw(&uniforms, vertices, "test", state);
+        renderer.flush(&mut framebuffer);
+        framebuffer.zbuffer.iter().filter(|&&depth| depth < 1000.0).count()
+    }
+
+    #[test]
+    fn cull_mode_and_winding_select_the_kept_faces() {
+        // Antihorario visto en pantalla, y el mismo triángulo en orden horario
+        let ccw = quad(1.0, 0.0)[..3].to_vec();
+        let cw = vec![ccw[0].clone(), ccw[2].clone(), ccw[1].clone()];
+
+        // (modo, cara frontal, se dibuja el antihorario, se dibuja el horario)
+        let cases = [
+            (CullMode::None, Winding::CounterClockwise, true, true),
+            (CullMode::Back, Winding::CounterClockwise, true, false),
+            (CullMode::Front, Winding::CounterClockwise, false, true),
+            (CullMode::None, Winding::Clockwise, true, true),
+            (CullMode::Back, Winding::Clockwise, false, true),
+            (CullMode::Front, Winding::Clockwise, true, false),
+        ];
+
+        for (cull_mode, front_face, keeps_ccw, keeps_cw) in cases {
+            let state = DrawState::default().with_cull_mode(cull_mode).with_front_face(front_face);
+            let kept_ccw = covered_pixels(&ccw, Mat4::identity(), &state) > 0;
+            let kept_cw = covered_pixels(&cw, Mat4::identity(), &state) > 0;
+            assert_eq!(kept_ccw, keeps_ccw, "{:?} / {:?}, triángulo antihorario", cull_mode, front_face);
+            assert_eq!(kept_cw, keeps_cw, "{:?} / {:?}, triángulo horario", cull_mode, front_face);
+        }
+    }
+
+    #[test]
+    fn mirrored_model_keeps_its_front_faces() {
+        // Espejado en X el triángulo queda horario en pantalla, pero sigue siendo su cara frontal
+        let triangle = quad(1.0, 0.0)[..3].to_vec();
+        let mirror = Mat4::new_nonuniform_scaling(&Vec3::new(-1.0, 1.0, 1.0));
+
+        assert!(covered_pixels(&triangle, mirror, &DrawState::default()) > 0);
+        assert_eq!(covered_pixels(&triangle, mirror, &DrawState::default().with_cull_mode(CullMode::Front)), 0);
+    }
+
+    #[test]
+    fn forward_shades_every_fragment() {
+        let (stats, visible) = render_occluded(false);
+
+        assert_eq!(visible, SIZE * SIZE);
+        assert_eq!(stats.shader_invocations, stats.fragments);
+        assert!(stats.shader_invocations > visible);
+    }
+}
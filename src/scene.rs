@@ -0,0 +1,523 @@
+use crate::framebuffer::Framebuffer;
+use crate::obj_loader::Model;
+use crate::vertex::Vertex;
+use crate::shaders::{body_color, create_model_matrix, create_viewport_matrix, Uniforms};
+use crate::camera::Camera;
+use crate::ring::create_ring_vertices;
+use crate::celestial::{SolarSystem, CelestialBody, Planet};
+use crate::effects::{create_orbit_lines, create_skybox_sphere, create_starfield};
+use crate::spacecraft::Spaceship;
+use crate::render::{DrawState, Renderer};
+use crate::framebuffer::BlendMode;
+use crate::point::{PointShape, PointSize};
+use crate::frustum::{BoundingSphere, Frustum};
+use crate::shadows::{RingOccluder, ShadowCasters, SphereOccluder};
+use crate::lighting::{Falloff, PointLight};
+use crate::color::Color;
+use crate::texture::{Encoding, Filter, SurfaceMap, Texture};
+use crate::options::SurfaceMapSpec;
+use crate::bake::load_or_bake;
+
+use nalgebra_glm::{Vec3, Vec4};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Ancho de las órbitas en píxeles de la ventana
+const ORBIT_LINE_WIDTH: f32 = 1.2;
+
+/// Segmentos por tramo de órbita: cada tramo se prueba contra el frustum
+const ORBIT_BATCH_SEGMENTS: usize = 16;
+
+/// Radios de los anillos de Saturno, relativos a la escala del planeta
+const RING_INNER_RADIUS: f32 = 1.2;
+pub const RING_OUTER_RADIUS: f32 = 1.8;
+
+/// Estrellas del fondo: cantidad, distancia como fracción del far plane
+/// (detrás de todo lo que se dibuja) y tamaño en píxeles de la ventana
+const STAR_COUNT: usize = 1500;
+const STAR_DISTANCE: f32 = 0.8;
+const STAR_SIZE: f32 = 1.6;
+
+/// Radio del skybox como fracción del far plane, detrás de las estrellas
+const SKY_DISTANCE: f32 = 0.9;
+
+/// Color de la luz del sol (blanco apenas cálido)
+const SUN_LIGHT_COLOR: Color = Color { r: 255, g: 246, b: 232, a: 255 };
+
+/// Con atenuación por cuadrado inverso, distancia al sol donde la luz vale 1
+/// (la órbita de la Tierra)
+const LIGHT_REFERENCE_DISTANCE: f32 = 7.5;
+
+/// Por debajo de este radio en pantalla (píxeles) un cuerpo se dibuja como
+/// punto en vez de malla: la esfera entera caería en uno o dos píxeles
+const DISTANT_BODY_PIXELS: f32 = 1.0;
+
+/// Tramo de órbita con su esfera envolvente
+pub struct LineBatch {
+    pub bounds: BoundingSphere,
+    pub vertices: Vec<Vertex>,
+}
+
+/// Todo lo necesario para dibujar un frame del sistema solar.
+/// Lo comparten la ventana interactiva y el modo headless.
+pub struct Scene {
+    pub solar_system: SolarSystem,
+    pub sphere_model: Model,
+    pub spaceship: Spaceship,
+    pub ring_vertices: Vec<Vertex>,
+    /// Esfera de radio 1 con las caras hacia adentro
+    pub sky_vertices: Vec<Vertex>,
+    pub orbit_batches: Vec<LineBatch>,
+    /// Radios envolventes de los modelos sin escalar
+    pub sphere_radius: f32,
+    pub spaceship_radius: f32,
+    pub stars: Vec<Vertex>,
+    pub show_spaceship: bool,
+    /// Atenuación de la luz del sol con la distancia
+    pub light_falloff: Falloff,
+    /// Usar los mapas horneados de los cuerpos (si se hornearon) en vez de
+    /// calcular todas las capas de sus shaders por píxel
+    pub use_baked_layers: bool,
+}
+
+impl Scene {
+    pub fn new() -> Result<Self, String> {
+        Self::with_solar_system(SolarSystem::new())
+    }
+
+    /// Escena de prueba de z-fighting (ver `SolarSystem::depth_test`)
+    pub fn depth_test() -> Result<Self, String> {
+        Self::with_solar_system(SolarSystem::depth_test())
+    }
+
+    fn with_solar_system(solar_system: SolarSystem) -> Result<Self, String> {
+        // Cargar modelo de esfera para planetas
+        let mut sphere_model = Model::load_from_file("assets/models/sphere.obj")
+            .map_err(|e| format!("No se pudo cargar sphere.obj: {}", e))?;
+        sphere_model.normalize_and_center(1.0);
+
+        // Cargar modelo de nave espacial
+        let spaceship_model = Model::load_from_file("assets/models/spaceship.obj")
+            .map_err(|e| format!("No se pudo cargar spaceship.obj: {}", e))?;
+        let mut spaceship = Spaceship::new(spaceship_model);
+        spaceship.scale = 0.05;
+
+        // Crear anillos de Saturno
+        let ring_vertices = create_ring_vertices(RING_INNER_RADIUS, RING_OUTER_RADIUS, 100);
+
+        // Crear geometría de órbitas, partida en tramos para el frustum culling
+        let orbit_batches = solar_system.planets
+            .iter()
+            .filter(|planet| planet.orbit.radius > 0.0)
+            .flat_map(|planet| {
+                let lines = create_orbit_lines(planet.orbit.radius, 160, planet.orbit.inclination);
+                lines
+                    .chunks(ORBIT_BATCH_SEGMENTS * 2)
+                    .map(|chunk| LineBatch {
+                        bounds: BoundingSphere::from_points(chunk.iter().map(|vertex| vertex.position)),
+                        vertices: chunk.to_vec(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let sphere_radius = sphere_model.bounding_radius();
+        let spaceship_radius = spaceship.model.bounding_radius();
+
+        let stars = create_starfield(STAR_COUNT, 1.0, 7);
+        let sky_vertices = create_skybox_sphere(1.0, 16, 32);
+
+        Ok(Scene {
+            solar_system,
+            sphere_model,
+            spaceship,
+            ring_vertices,
+            sky_vertices,
+            orbit_batches,
+            sphere_radius,
+            spaceship_radius,
+            stars,
+            show_spaceship: true,
+            light_falloff: Falloff::None,
+            use_baked_layers: true,
+        })
+    }
+
+    /// Activa o desactiva la atenuación por cuadrado inverso de la luz del sol
+    pub fn set_inverse_square(&mut self, enabled: bool) {
+        self.light_falloff = if enabled {
+            Falloff::InverseSquare { reference_distance: LIGHT_REFERENCE_DISTANCE }
+        } else {
+            Falloff::None
+        };
+    }
+
+    /// Hornea las capas estáticas del shader de cada cuerpo en un mapa de
+    /// `width`×`width/2` (uno por tipo de shader, compartido), leyéndolos de
+    /// `cache_dir` si ya están guardados ahí
+    pub fn bake_static_layers(&mut self, width: usize, cache_dir: Option<&Path>, threads: usize) -> Result<(), String> {
+        let mut shader_types: Vec<String> = Vec::new();
+        self.for_each_body(|body| {
+            if !shader_types.contains(&body.shader_type) {
+                shader_types.push(body.shader_type.clone());
+            }
+        });
+
+        let mut maps = Vec::new();
+        for shader_type in shader_types {
+            let start = Instant::now();
+            if let Some((texture, cached)) = load_or_bake(&shader_type, width, cache_dir, threads)? {
+                let source = if cached { "leído de la caché" } else { "horneado" };
+                println!("🔥 Mapa de {}: {}x{} {} en {:.2} s",
+                         shader_type, texture.width(), texture.height(), source, start.elapsed().as_secs_f32());
+                maps.push((shader_type, Arc::new(texture)));
+            }
+        }
+
+        self.for_each_body(|body| {
+            body.baked_layers = maps
+                .iter()
+                .find(|(shader_type, _)| *shader_type == body.shader_type)
+                .map(|(_, texture)| Arc::clone(texture));
+        });
+        Ok(())
+    }
+
+    /// Recorre el sol, los planetas y sus satélites
+    fn for_each_body(&mut self, mut visit: impl FnMut(&mut Planet)) {
+        let system = &mut self.solar_system;
+        visit(&mut system.sun);
+        for planet in &mut system.planets {
+            visit(planet);
+            planet.satellites.iter_mut().for_each(&mut visit);
+        }
+    }
+
+    /// Mapa horneado que recibe el shader del cuerpo, si están activados
+    fn baked_layers(&self, body: &Planet) -> Option<Arc<Texture>> {
+        body.baked_layers.clone().filter(|_| self.use_baked_layers)
+    }
+
+    /// Carga las texturas pedidas con `--surface-map` y las asigna a sus cuerpos
+    pub fn load_surface_maps(&mut self, specs: &[SurfaceMapSpec], filter: Filter) -> Result<(), String> {
+        for spec in specs {
+            let texture = Texture::load(&spec.path, Encoding::Srgb)?;
+            println!("🗺️  Mapa de superficie de {}: {} ({}x{}, {} niveles de mip, mezcla {:.2})",
+                     spec.body, spec.path.display(), texture.width(), texture.height(), texture.mip_levels(), spec.blend);
+            let surface_map = SurfaceMap::new(Arc::new(texture), spec.blend).with_filter(filter);
+            self.set_surface_map(&spec.body, surface_map)?;
+        }
+        Ok(())
+    }
+
+    /// Pone un mapa de superficie al sol, planeta o satélite llamado `name`
+    pub fn set_surface_map(&mut self, name: &str, surface_map: SurfaceMap) -> Result<(), String> {
+        let system = &mut self.solar_system;
+        let body = std::iter::once(&mut system.sun)
+            .chain(system.planets.iter_mut())
+            .find_map(|body| find_body(body, name))
+            .ok_or_else(|| format!("No hay ningún cuerpo llamado {}", name))?;
+
+        body.surface_map = Some(surface_map);
+        Ok(())
+    }
+
+    /// Avanza la simulación un paso de tiempo
+    pub fn update(&mut self, delta_time: f32) {
+        self.solar_system.update(delta_time);
+        self.spaceship.update(delta_time);
+    }
+
+    /// Dibuja cielo, estrellas, órbitas, sol, planetas (con satélites y anillos) y la nave
+    pub fn render(&self, renderer: &mut Renderer, framebuffer: &mut Framebuffer, camera: &Camera, time: f32) {
+        let width = framebuffer.width as f32;
+        let height = framebuffer.height as f32;
+
+        // Configurar matrices de vista
+        let view_matrix = camera.get_view_matrix();
+        let projection_matrix = camera.get_projection_matrix(width / height);
+        let viewport_matrix = create_viewport_matrix(width, height);
+        let frustum = Frustum::from_matrix(&(projection_matrix * view_matrix));
+        let lights = Arc::new(self.lights());
+        let shadows = Arc::new(self.shadow_casters());
+
+        // Los anillos se ven por ambos lados y cubren parcialmente
+        let translucent = DrawState::double_sided().with_blend_mode(BlendMode::Alpha);
+
+        // 0. CIELO: la esfera de `create_skybox_sphere` tiene el orden de vértices
+        // invertido, así que vista desde adentro sus caras ya son frontales.
+        // Suma la Vía Láctea al fondo.
+        let sky_uniforms = Uniforms {
+            model_matrix: create_model_matrix(camera.eye, camera.far * SKY_DISTANCE, Vec3::zeros()),
+            view_matrix,
+            projection_matrix,
+            viewport_matrix,
+            time,
+            camera_position: camera.eye,
+            lights: Arc::clone(&lights),
+            shadows: Arc::clone(&shadows),
+            surface_map: None,
+            baked_layers: None,
+        };
+        let sky = DrawState::default().with_blend_mode(BlendMode::Additive);
+        renderer.draw(&sky_uniforms, &self.sky_vertices, "skybox", &sky);
+
+        // Las estrellas siguen a la cámara, así que parecen infinitamente lejanas
+        let star_uniforms = Uniforms {
+            model_matrix: create_model_matrix(camera.eye, camera.far * STAR_DISTANCE, Vec3::zeros()),
+            view_matrix,
+            projection_matrix,
+            viewport_matrix,
+            time,
+            camera_position: camera.eye,
+            lights: Arc::clone(&lights),
+            shadows: Arc::clone(&shadows),
+            surface_map: None,
+            baked_layers: None,
+        };
+        let additive = DrawState::default().with_blend_mode(BlendMode::Additive);
+        renderer.draw_points(&star_uniforms, &self.stars, PointSize::Pixels(STAR_SIZE), PointShape::Disc, &additive);
+
+        // 1. RENDERIZAR ÓRBITAS (cada tramo solo si toca el frustum)
+        let orbit_uniforms = Uniforms {
+            model_matrix: create_model_matrix(Vec3::zeros(), 1.0, Vec3::zeros()),
+            view_matrix,
+            projection_matrix,
+            viewport_matrix,
+            time,
+            camera_position: camera.eye,
+            lights: Arc::clone(&lights),
+            shadows: Arc::clone(&shadows),
+            surface_map: None,
+            baked_layers: None,
+        };
+
+        for batch in &self.orbit_batches {
+            if renderer.is_visible(&frustum, &batch.bounds) {
+                renderer.draw_lines(&orbit_uniforms, &batch.vertices, ORBIT_LINE_WIDTH, &DrawState::default());
+            }
+        }
+
+        // 2. RENDERIZAR EL SOL
+        let sun = &self.solar_system.sun;
+        let sun_model_matrix = create_model_matrix(
+            sun.get_position(),
+            sun.get_scale(),
+            sun.get_rotation()
+        );
+
+        let sun_uniforms = Uniforms {
+            model_matrix: sun_model_matrix,
+            view_matrix,
+            projection_matrix,
+            viewport_matrix,
+            time,
+            camera_position: camera.eye,
+            lights: Arc::clone(&lights),
+            shadows: Arc::clone(&shadows),
+            surface_map: sun.surface_map.clone(),
+            baked_layers: self.baked_layers(sun),
+        };
+
+        let sun_bounds = BoundingSphere::new(sun.get_position(), sun.get_scale() * self.sphere_radius);
+        if renderer.is_visible(&frustum, &sun_bounds) {
+            renderer.draw_indexed(&sun_uniforms, &self.sphere_model.vertices, &self.sphere_model.indices, sun.get_shader(), &DrawState::default());
+        }
+
+        // 3. RENDERIZAR TODOS LOS PLANETAS
+        // El recorte contra el near plane permite acercarse sin saltar cuerpos
+        for planet in &self.solar_system.planets {
+            let planet_model_matrix = create_model_matrix(
+                planet.get_position(),
+                planet.get_scale(),
+                planet.get_rotation()
+            );
+
+            let planet_uniforms = Uniforms {
+                model_matrix: planet_model_matrix,
+                view_matrix,
+                projection_matrix,
+                viewport_matrix,
+                time,
+                camera_position: camera.eye,
+                lights: Arc::clone(&lights),
+                shadows: Arc::clone(&shadows),
+                surface_map: planet.surface_map.clone(),
+                baked_layers: self.baked_layers(planet),
+            };
+
+            self.draw_body(renderer, &frustum, planet, &planet_uniforms, camera, height);
+
+            // Renderizar satélites (Luna)
+            for satellite in &planet.satellites {
+                let sat_model_matrix = create_model_matrix(
+                    satellite.get_position(),
+                    satellite.get_scale(),
+                    satellite.get_rotation()
+                );
+
+                let sat_uniforms = Uniforms {
+                    model_matrix: sat_model_matrix,
+                    view_matrix,
+                    projection_matrix,
+                    viewport_matrix,
+                    time,
+                    camera_position: camera.eye,
+                    lights: Arc::clone(&lights),
+                    shadows: Arc::clone(&shadows),
+                    surface_map: satellite.surface_map.clone(),
+                    baked_layers: self.baked_layers(satellite),
+                };
+
+                self.draw_body(renderer, &frustum, satellite, &sat_uniforms, camera, height);
+            }
+
+            // Renderizar anillos de Saturno
+            let ring_bounds = BoundingSphere::new(planet.get_position(), planet.get_scale() * RING_OUTER_RADIUS);
+            if planet.name == "Saturno" && renderer.is_visible(&frustum, &ring_bounds) {
+                let ring_model_matrix = create_model_matrix(
+                    planet.get_position(),
+                    planet.get_scale(),
+                    ring_rotation(planet)
+                );
+
+                let ring_uniforms = Uniforms {
+                    model_matrix: ring_model_matrix,
+                    view_matrix,
+                    projection_matrix,
+                    viewport_matrix,
+                    time,
+                    camera_position: camera.eye,
+                    lights: Arc::clone(&lights),
+                    shadows: Arc::clone(&shadows),
+                    surface_map: None,
+                    baked_layers: None,
+                };
+
+                renderer.draw(&ring_uniforms, &self.ring_vertices, "ring", &translucent);
+            }
+        }
+
+        // 4. RENDERIZAR NAVE ESPACIAL (va delante de la cámara, pero se prueba igual)
+        let ship_position = self.spaceship.get_position(camera);
+        let ship_bounds = BoundingSphere::new(ship_position, self.spaceship.scale * self.spaceship_radius);
+        if self.show_spaceship && renderer.is_visible(&frustum, &ship_bounds) {
+            let ship_rotation = self.spaceship.get_rotation(camera);
+
+            let ship_model_matrix = create_model_matrix(
+                ship_position,
+                self.spaceship.scale,
+                ship_rotation
+            );
+
+            let ship_uniforms = Uniforms {
+                model_matrix: ship_model_matrix,
+                view_matrix,
+                projection_matrix,
+                viewport_matrix,
+                time,
+                camera_position: camera.eye,
+                lights: Arc::clone(&lights),
+                shadows: Arc::clone(&shadows),
+                surface_map: None,
+                baked_layers: None,
+            };
+
+            renderer.draw_indexed(&ship_uniforms, &self.spaceship.model.vertices, &self.spaceship.model.indices, "spaceship", &DrawState::default());
+        }
+
+        renderer.flush(framebuffer);
+    }
+
+    /// Luces del frame: el sol como luz puntual en su posición actual
+    fn lights(&self) -> Vec<PointLight> {
+        let sun = &self.solar_system.sun;
+        vec![
+            PointLight::new(sun.get_position(), SUN_LIGHT_COLOR, 1.0)
+                .with_radius(sun.get_scale() * self.sphere_radius)
+                .with_falloff(self.light_falloff),
+        ]
+    }
+
+    /// Oclusores del sol para este frame: las esferas de planetas y satélites
+    /// y los anillos, en sus posiciones actuales
+    fn shadow_casters(&self) -> ShadowCasters {
+        let mut casters = ShadowCasters::new();
+
+        for planet in &self.solar_system.planets {
+            for body in std::iter::once(planet).chain(&planet.satellites) {
+                casters.spheres.push(SphereOccluder {
+                    center: body.get_position(),
+                    radius: body.get_scale() * self.sphere_radius,
+                });
+            }
+
+            if planet.name == "Saturno" {
+                let rotation = create_model_matrix(Vec3::zeros(), 1.0, ring_rotation(planet));
+                casters.rings.push(RingOccluder {
+                    center: planet.get_position(),
+                    normal: (rotation * Vec4::new(0.0, 1.0, 0.0, 0.0)).xyz().normalize(),
+                    inner_radius: planet.get_scale() * RING_INNER_RADIUS,
+                    outer_radius: planet.get_scale() * RING_OUTER_RADIUS,
+                });
+            }
+        }
+
+        casters
+    }
+
+    /// Dibuja un planeta o satélite como esfera, o como un punto de su color
+    /// medio si está tan lejos que la malla sería más chica que un píxel.
+    /// Fuera del frustum no envía nada.
+    fn draw_body(
+        &self,
+        renderer: &mut Renderer,
+        frustum: &Frustum,
+        body: &Planet,
+        uniforms: &Uniforms,
+        camera: &Camera,
+        height: f32,
+    ) {
+        let radius = body.get_scale() * self.sphere_radius;
+        if !renderer.is_visible(frustum, &BoundingSphere::new(body.get_position(), radius)) {
+            return;
+        }
+
+        let distance = (body.get_position() - camera.eye).magnitude();
+        let focal = uniforms.projection_matrix[(1, 1)] * height * 0.5;
+        let screen_radius = radius * focal / distance.max(1e-3);
+
+        // El umbral es en píxeles de la ventana; con SSAA el framebuffer es más grande
+        if screen_radius >= DISTANT_BODY_PIXELS * renderer.pixel_scale {
+            renderer.draw_indexed(uniforms, &self.sphere_model.vertices, &self.sphere_model.indices, body.get_shader(), &DrawState::default());
+            return;
+        }
+
+        let center = Vertex::new_with_color(body.get_position(), body_color(body.get_shader()));
+        let point_uniforms = Uniforms {
+            model_matrix: create_model_matrix(Vec3::zeros(), 1.0, Vec3::zeros()),
+            ..uniforms.clone()
+        };
+        renderer.draw_points(
+            &point_uniforms,
+            std::slice::from_ref(&center),
+            PointSize::World(radius),
+            PointShape::Disc,
+            &DrawState::default(),
+        );
+    }
+}
+
+/// Busca `name` (sin distinguir mayúsculas) en el cuerpo y sus satélites
+fn find_body<'a>(body: &'a mut Planet, name: &str) -> Option<&'a mut Planet> {
+    if body.name.eq_ignore_ascii_case(name) {
+        return Some(body);
+    }
+    body.satellites.iter_mut().find_map(|satellite| find_body(satellite, name))
+}
+
+/// Inclinación fija de los anillos, girando con el planeta
+fn ring_rotation(planet: &Planet) -> Vec3 {
+    Vec3::new(0.4, planet.rotation.y, 0.0)
+}
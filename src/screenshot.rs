@@ -0,0 +1,79 @@
+use crate::image_io::{write_image, ImageFormat};
+use crate::options::Options;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Capturas de la ventana: dónde se guardan, en qué formato y a cuántas
+/// veces la resolución de la ventana (más de 1 re-renderiza el frame fuera
+/// de pantalla)
+pub struct Screenshots {
+    pub dir: PathBuf,
+    pub format: ImageFormat,
+    pub scale: usize,
+}
+
+impl Screenshots {
+    pub fn from_options(options: &Options) -> Self {
+        Screenshots {
+            dir: options.screenshot_dir.clone(),
+            format: options.screenshot_format,
+            scale: options.screenshot_scale,
+        }
+    }
+
+    /// Guarda un buffer 0x00RRGGBB con un nombre nuevo y devuelve su ruta
+    pub fn save(&self, buffer: &[u32], width: usize, height: usize) -> Result<PathBuf, String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("No se pudo crear {}: {}", self.dir.display(), e))?;
+        let stem = format!("captura_{}", timestamp(SystemTime::now()));
+        let path = unique_path(&self.dir, &stem, self.format.extension());
+        write_image(&path, buffer, width, height, self.format)?;
+        Ok(path)
+    }
+}
+
+/// `<nombre>.<ext>` dentro de `dir`, o con `_2`, `_3`... si ya existe (sin
+/// extensión para directorios)
+pub fn unique_path(dir: &Path, stem: &str, extension: &str) -> PathBuf {
+    let file_name = |stem: &str| if extension.is_empty() { stem.to_string() } else { format!("{}.{}", stem, extension) };
+    let mut path = dir.join(file_name(stem));
+    let mut index = 2;
+    while path.exists() {
+        path = dir.join(file_name(&format!("{}_{}", stem, index)));
+        index += 1;
+    }
+    path
+}
+
+/// Fecha y hora UTC como `AAAA-MM-DD_HH-MM-SS` (ordenable y válida en
+/// cualquier sistema de archivos)
+pub fn timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as i64;
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let second_of_day = seconds.rem_euclid(86_400);
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        second_of_day / 3600,
+        second_of_day / 60 % 60,
+        second_of_day % 60
+    )
+}
+
+/// Año, mes y día de un día contado desde 1970-01-01 (calendario gregoriano)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -1,15 +1,40 @@
 use nalgebra_glm::{Vec3, Vec4, Mat4, Vec2};
 use crate::vertex::Vertex;
 use crate::fragment::Fragment;
-use crate::color::Color;
+use crate::color::{Color, HdrColor};
+use crate::varyings::{self, Varyings};
+use crate::shadows::ShadowCasters;
+use crate::lighting::{apply_lighting, illuminate, LightSample, PointLight};
+use crate::texture::{Filter, Sampler, SurfaceMap, Texture};
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct Uniforms {
     pub model_matrix: Mat4,
     pub view_matrix: Mat4,
     pub projection_matrix: Mat4,
     pub viewport_matrix: Mat4,
     pub time: f32,
-    pub light_dir: Vec3,
+    /// Posición de la cámara en el mundo, para el vector de vista por píxel
+    pub camera_position: Vec3,
+    /// Luces puntuales en espacio de mundo (el sol)
+    pub lights: Arc<Vec<PointLight>>,
+    /// Cuerpos y anillos que pueden tapar las luces en este frame
+    pub shadows: Arc<ShadowCasters>,
+    /// Textura equirectangular del cuerpo que se mezcla con su shader
+    pub surface_map: Option<SurfaceMap>,
+    /// Capas estáticas del shader horneadas (ver `bake`); sin ellas se calculan por píxel
+    pub baked_layers: Option<Arc<Texture>>,
+}
+
+/// Capas de un shader que no cambian con el tiempo ni con la vista, en una
+/// dirección de la esfera: su color y un valor que usan las capas animadas
+/// (la granulación del sol, dónde hay océano en la Tierra...). Se hornean en
+/// un mapa equirectangular con `mask` en el canal alfa.
+#[derive(Clone, Copy, Debug)]
+pub struct StaticLayers {
+    pub color: Color,
+    pub mask: f32,
 }
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
@@ -20,17 +45,8 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         1.0
     );
 
-    let transformed = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
-
-    let w = transformed.w;
-    let ndc_position = Vec4::new(
-        transformed.x / w,
-        transformed.y / w,
-        transformed.z / w,
-        1.0
-    );
-
-    let screen_position = uniforms.viewport_matrix * ndc_position;
+    // Posición en clip space; la división por w se hace después del recorte
+    let clip_position = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
 
     let model_mat3 = Mat4::new(
         uniforms.model_matrix[(0, 0)], uniforms.model_matrix[(0, 1)], uniforms.model_matrix[(0, 2)], 0.0,
@@ -43,9 +59,20 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     let transformed_normal = model_mat3 * normal4;
     let final_normal = Vec3::new(transformed_normal.x, transformed_normal.y, transformed_normal.z).normalize();
 
+    let world_position = uniforms.model_matrix * position;
+
+    // Atributos que el rasterizador interpola con corrección de perspectiva
+    let mut vertex_varyings = Varyings::new();
+    vertex_varyings.set_vec3(varyings::WORLD_POSITION, world_position.xyz());
+    vertex_varyings.set_vec3(varyings::NORMAL, final_normal);
+    vertex_varyings.set_vec2(varyings::TEX_COORDS, vertex.tex_coords);
+    vertex_varyings.set_color(varyings::COLOR, vertex.color);
+    vertex_varyings.set_vec3(varyings::OBJECT_POSITION, vertex.position);
+
     let mut new_vertex = vertex.clone();
-    new_vertex.transformed_position = Vec3::new(screen_position.x, screen_position.y, screen_position.z);
+    new_vertex.clip_position = clip_position;
     new_vertex.transformed_normal = final_normal;
+    new_vertex.varyings = vertex_varyings;
 
     new_vertex
 }
@@ -54,7 +81,124 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
 // SISTEMA DE FRAGMENT SHADERS PARA PLANETAS
 // ============================================
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, shader_type: &str) -> Color {
+/// Brillo del sol relativo al blanco de los demás shaders. Lo aplica su
+/// shader en espacio lineal, así queda por encima de 1.0 en el buffer HDR y el
+/// tone mapping lo comprime sin aplanarlo.
+pub const SUN_EMISSION: f32 = 1.6;
+
+/// Luz ambiente de los mapas de superficie, para que el lado nocturno no
+/// quede negro del todo (como en los shaders procedurales)
+const SURFACE_MAP_AMBIENT: f32 = 0.15;
+
+/// Ejecuta el shader del cuerpo y devuelve su color en espacio lineal
+pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, shader_type: &str) -> HdrColor {
+    let color = shade_surface(fragment, uniforms, shader_type);
+    let Some(map) = &uniforms.surface_map else {
+        return color;
+    };
+
+    // El sol emite luz propia: su mapa no se ilumina, brilla como el shader
+    let emission = (shader_type == "sun").then_some(SUN_EMISSION);
+    apply_surface_map(color, map, fragment, uniforms, emission)
+}
+
+/// Luz directa que recibe el fragmento desde las luces de la escena, con
+/// sombras y eclipses
+fn light_at(fragment: &Fragment, normal: &Vec3, uniforms: &Uniforms) -> LightSample {
+    illuminate(&uniforms.lights, &uniforms.shadows, &fragment.world_position, normal)
+}
+
+/// Mezcla el mapa de superficie sobre el color procedural. El mapa se
+/// muestrea con la dirección en espacio de objeto, así gira con el cuerpo, y
+/// se ilumina con las mismas luces y sombras que el shader, salvo que el
+/// cuerpo emita luz propia con brillo `emission`.
+fn apply_surface_map(color: HdrColor, map: &SurfaceMap, fragment: &Fragment, uniforms: &Uniforms, emission: Option<f32>) -> HdrColor {
+    let direction = fragment.varyings.get_vec3(varyings::OBJECT_POSITION);
+    if direction.magnitude() < 1e-6 {
+        return color;
+    }
+
+    let uv = get_uv_from_position(&direction.normalize());
+    let texel = map.texture.sample(uv, &map.sampler, texture_lod(&map.texture, fragment, uniforms));
+
+    let surface = match emission {
+        Some(emission) => texel * emission,
+        None => {
+            let light = light_at(fragment, &fragment.normal, uniforms);
+            apply_lighting(texel, SURFACE_MAP_AMBIENT, light.diffuse, light.color)
+        }
+    };
+    let surface = HdrColor { a: color.a, ..surface };
+
+    color.lerp(&surface, map.blend * texel.a)
+}
+
+/// Nivel de mip para una textura equirectangular sobre el cuerpo: cuántos
+/// texels cubre un píxel de pantalla, a partir del tamaño de un píxel a la
+/// distancia del fragmento, el radio del cuerpo y cuánto se inclina la
+/// superficie respecto a la vista
+fn texture_lod(texture: &Texture, fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    let distance = (uniforms.camera_position - fragment.world_position).magnitude();
+    let focal = (uniforms.projection_matrix[(1, 1)] * uniforms.viewport_matrix[(1, 1)]).abs();
+    let scale = uniforms.model_matrix.column(0).xyz().magnitude();
+    let radius = fragment.varyings.get_vec3(varyings::OBJECT_POSITION).magnitude() * scale;
+    if focal <= 0.0 || radius <= 0.0 {
+        return 0.0;
+    }
+
+    let facing = fragment.normal.dot(&view_direction(fragment, uniforms)).abs().max(0.1);
+    let texels_per_radian = (texture.width() as f32 / (2.0 * std::f32::consts::PI))
+        .max(texture.height() as f32 / std::f32::consts::PI);
+    let texels = distance / focal / (radius * facing) * texels_per_radian;
+    texels.max(1.0).log2()
+}
+
+/// Función que evalúa las capas estáticas de un tipo de shader en una
+/// dirección de la esfera, o `None` si el shader no tiene nada que hornear
+pub fn static_layers(shader_type: &str) -> Option<fn(&Vec3) -> StaticLayers> {
+    let layers: fn(&Vec3) -> StaticLayers = match shader_type {
+        "sun" => sun_static_layers,
+        "rocky_mars" => mars_static_layers,
+        "rocky_earth" => earth_static_layers,
+        "gas_jupiter" => jupiter_static_layers,
+        "gas_saturn" => saturn_static_layers,
+        "ice_neptune" => neptune_static_layers,
+        "moon" => moon_static_layers,
+        _ => return None,
+    };
+    Some(layers)
+}
+
+/// Capas estáticas del fragmento: del mapa horneado del cuerpo si lo tiene
+/// (con la misma proyección que `get_uv_from_position`), o calculadas
+fn static_layers_at(fragment: &Fragment, uniforms: &Uniforms, layers: fn(&Vec3) -> StaticLayers) -> StaticLayers {
+    let Some(texture) = &uniforms.baked_layers else {
+        return layers(&fragment.normal);
+    };
+
+    let uv = get_uv_from_position(&fragment.normal);
+    let sampler = Sampler::equirectangular(Filter::Trilinear);
+    let texel = texture.sample(uv, &sampler, texture_lod(texture, fragment, uniforms));
+    // El mapa guarda los bytes sRGB tal cual (`Encoding::Raw`)
+    let to_byte = |value: f32| (value * 255.0 + 0.5) as u8;
+    StaticLayers {
+        color: Color::new(to_byte(texel.r), to_byte(texel.g), to_byte(texel.b)),
+        mask: texel.a,
+    }
+}
+
+/// Dirección del fragmento hacia la cámara, en espacio de mundo
+fn view_direction(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+    let to_camera = uniforms.camera_position - fragment.world_position;
+    let distance = to_camera.magnitude();
+    if distance > 1e-6 {
+        to_camera / distance
+    } else {
+        fragment.normal
+    }
+}
+
+fn shade_surface(fragment: &Fragment, uniforms: &Uniforms, shader_type: &str) -> HdrColor {
     match shader_type {
         "sun" => sun_shader(fragment, uniforms),
         "rocky_mars" => mars_shader(fragment, uniforms),
@@ -64,48 +208,60 @@ pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, shader_type: &s
         "ice_neptune" => neptune_shader(fragment, uniforms),
         "moon" => moon_shader(fragment, uniforms),
         "ring" => ring_shader(fragment, uniforms),
-        "orbit" => orbit_shader(fragment, uniforms),
         "spaceship" => spaceship_shader(fragment, uniforms),
+        "skybox" => skybox_shader(fragment, uniforms),
         "test" => test_shader(fragment, uniforms),
         _ => default_shader(fragment, uniforms)
     }
 }
 
+/// Color medio de cada tipo de cuerpo, para dibujarlo como un punto cuando
+/// está tan lejos que su malla ocuparía menos de un píxel
+pub fn body_color(shader_type: &str) -> Color {
+    match shader_type {
+        "sun" => Color::new(255, 220, 120),
+        "rocky_mars" => Color::new(190, 90, 50),
+        "rocky_earth" => Color::new(70, 120, 190),
+        "gas_jupiter" => Color::new(200, 170, 130),
+        "gas_saturn" => Color::new(220, 200, 150),
+        "ice_neptune" => Color::new(80, 120, 220),
+        "moon" => Color::new(160, 160, 160),
+        _ => Color::new(200, 200, 200),
+    }
+}
+
 // ============================================
 // SHADER DE PRUEBA (Con iluminación mejorada)
 // ============================================
-fn test_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let light_dir = uniforms.light_dir.normalize();
+fn test_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
     
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.2;
-    let intensity = ambient + diffuse * 0.8;
+    let light = light_at(fragment, &normal, uniforms);
+    let ambient = 0.03;
     
     let base_color = Color::new(255, 255, 0);
-    base_color * intensity
+    apply_lighting(HdrColor::from(base_color), ambient, light.diffuse * 0.8, light.color)
 }
 
 // ============================================
 // SHADER DEFAULT (Con iluminación)
 // ============================================
-fn default_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let light_dir = uniforms.light_dir.normalize();
+fn default_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
     
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.2;
-    let intensity = ambient + diffuse * 0.8;
+    let light = light_at(fragment, &normal, uniforms);
+    let ambient = 0.03;
     
-    fragment.color * intensity
+    apply_lighting(HdrColor::from(fragment.color), ambient, light.diffuse * 0.8, light.color)
 }
 
 // ============================================
 // SHADER: SOL (Estrella) - 5 CAPAS
 // ============================================
-fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let normal = fragment.normal.normalize();
-    let uv = get_uv_from_position(&fragment.normal);
+/// Capas estáticas del sol: el color base y, como máscara, el ruido de la
+/// granulación (se aplica después de las manchas, que se mueven)
+fn sun_static_layers(normal: &Vec3) -> StaticLayers {
+    let uv = get_uv_from_position(normal);
     
     // CAPA 1: Color Base Amarillo-Naranja Brillante
     let core_yellow = Color::new(255, 220, 100);
@@ -122,6 +278,15 @@ fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         core_yellow
     };
     
+    StaticLayers { color: base_color, mask: fbm(uv.x * 40.0, uv.y * 40.0, 4) }
+}
+
+fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
+    let normal = fragment.normal.normalize();
+    let uv = get_uv_from_position(&fragment.normal);
+    let layers = static_layers_at(fragment, uniforms, sun_static_layers);
+    let base_color = layers.color;
+    
     // CAPA 2: Manchas Solares (Sunspots)
     let sunspot_color = Color::new(180, 100, 40);
     let sunspot_core = Color::new(120, 60, 20);
@@ -149,7 +314,7 @@ fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let granule_bright = Color::new(255, 230, 120);
     let granule_dark = Color::new(240, 190, 90);
     
-    let granulation = fbm(uv.x * 40.0, uv.y * 40.0, 4);
+    let granulation = layers.mask;
     
     let color_with_granulation = if granulation > 0.52 {
         let gran_factor = (granulation - 0.52) * 2.0;
@@ -185,7 +350,7 @@ fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // CAPA 5: Corona Solar
     let corona_color = Color::new(255, 200, 100);
     
-    let view_dir = Vec3::new(0.0, 0.0, 1.0);
+    let view_dir = view_direction(fragment, uniforms);
     let fresnel = 1.0 - nalgebra_glm::dot(&normal, &view_dir).abs();
     let fresnel_pow = fresnel.powf(2.0);
     
@@ -204,23 +369,18 @@ fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         color_with_flares
     };
     
-    // Brillo propio
+    // Brillo propio: la emisión lleva al sol por encima de 1.0 sin recortarlo
     let brightness_boost = 1.15;
     
-    Color::new(
-        (color_with_corona.r as f32 * brightness_boost).min(255.0) as u8,
-        (color_with_corona.g as f32 * brightness_boost).min(255.0) as u8,
-        (color_with_corona.b as f32 * brightness_boost).min(255.0) as u8,
-    )
+    HdrColor::from(color_with_corona) * (brightness_boost * SUN_EMISSION)
 }
 
 // ============================================
 // SHADER: MARTE (Planeta Rocoso) - 4 CAPAS
 // ============================================
-fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
-    let uv = get_uv_from_position(&fragment.normal);
+/// Marte no tiene capas animadas: todo menos la iluminación es estático
+fn mars_static_layers(normal: &Vec3) -> StaticLayers {
+    let uv = get_uv_from_position(normal);
     
     // CAPA 1: Color Base Rojo-Naranja
     let rust_color = Color::new(193, 68, 14);
@@ -269,7 +429,7 @@ fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     
     // CAPA 3: Casquetes Polares
     let polar_threshold = 0.87;
-    let latitude = fragment.normal.y.abs();
+    let latitude = normal.y.abs();
     let ice_color = Color::new(245, 248, 255);
     
     let color_with_poles = if latitude > polar_threshold {
@@ -296,19 +456,24 @@ fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         color_with_poles
     };
     
+    StaticLayers { color: color_with_terrain, mask: 1.0 }
+}
+
+fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
+    let normal = fragment.normal.normalize();
+    let color_with_terrain = static_layers_at(fragment, uniforms, mars_static_layers).color;
+    
     // Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.3;
-    let intensity = ambient + diffuse * 0.7;
+    let light = light_at(fragment, &normal, uniforms);
+    let ambient = 0.07;
     
-    color_with_terrain * intensity
+    apply_lighting(HdrColor::from(color_with_terrain), ambient, light.diffuse * 0.7, light.color)
 }
 
-fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
-    let view_dir = Vec3::new(0.0, 0.0, 1.0);
-    let uv = get_uv_from_position(&fragment.normal);
+/// Capas estáticas de la Tierra: océanos, continentes y polos, con la
+/// máscara de océano que usa el reflejo especular
+fn earth_static_layers(normal: &Vec3) -> StaticLayers {
+    let uv = get_uv_from_position(normal);
 
     // ============================================================
     // CAPA 1: OCÉANOS
@@ -361,7 +526,7 @@ fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // ============================================================
     let ice_color = Color::new(250, 250, 255);
     let ice_shadow = Color::new(220, 230, 245);
-    let latitude = fragment.normal.y.abs();
+    let latitude = normal.y.abs();
     let polar_start = 0.77;
 
     let mut color_with_poles = color_with_land;
@@ -372,6 +537,21 @@ fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         color_with_poles = mix_color(&color_with_land, &ice_mix, pole_factor);
     }
 
+    // Aplicamos el especular solo en regiones oceánicas (usando inverso del threshold)
+    let ocean_factor = smoothstep(0.6, land_threshold - 0.05, continent_noise);
+
+    StaticLayers { color: color_with_poles, mask: ocean_factor }
+}
+
+fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
+    let normal = fragment.normal.normalize();
+    let light = light_at(fragment, &normal, uniforms);
+    let light_dir = light.direction;
+    let view_dir = view_direction(fragment, uniforms);
+    let uv = get_uv_from_position(&fragment.normal);
+    let layers = static_layers_at(fragment, uniforms, earth_static_layers);
+    let color_with_poles = layers.color;
+
     // ============================================================
     // CAPA 4: NUBES
     // ============================================================
@@ -401,32 +581,28 @@ fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // EFECTO ESPECULAR sobre el océano
     // ============================================================
     let half_dir = (light_dir + view_dir).normalize();
-    let spec = nalgebra_glm::dot(&normal, &half_dir).max(0.0).powf(80.0);
+    // Solo donde llega luz directa (lado diurno y fuera de eclipses)
+    let spec = nalgebra_glm::dot(&normal, &half_dir).max(0.0).powf(80.0) * light.diffuse.min(1.0);
     let specular_strength = 0.5;
-    let specular_color = Color::new(180, 220, 255) * (spec * specular_strength);
+    let specular_color = HdrColor::from(Color::new(180, 220, 255)) * (spec * specular_strength);
 
-    // Aplicamos solo en regiones oceánicas (usando inverso del threshold)
-    let ocean_factor = smoothstep(0.6, land_threshold - 0.05, continent_noise);
-    let color_with_specular = color_with_atmosphere + (specular_color * ocean_factor);
+    let color_with_specular = HdrColor::from(color_with_atmosphere) + specular_color * layers.mask;
 
     // ============================================================
     // ILUMINACIÓN FINAL
     // ============================================================
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.22;
-    let intensity = ambient + diffuse * 0.78;
+    let ambient = 0.04;
 
-    color_with_specular * intensity
+    apply_lighting(color_with_specular, ambient, light.diffuse * 0.78, light.color)
 }
 
 
 // ============================================
 // SHADER: JÚPITER (Gigante Gaseoso) - 4 CAPAS
 // ============================================
-fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
-    let uv = get_uv_from_position(&fragment.normal);
+/// Capas estáticas de Júpiter: las bandas (la turbulencia y la mancha se mueven)
+fn jupiter_static_layers(normal: &Vec3) -> StaticLayers {
+    let uv = get_uv_from_position(normal);
     
     // CAPA 1: Bandas Horizontales
     let band_color_1 = Color::new(220, 190, 160);
@@ -434,7 +610,7 @@ fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let band_color_3 = Color::new(140, 100, 70);
     let band_color_4 = Color::new(200, 170, 130);
     
-    let y_coord = fragment.normal.y;
+    let y_coord = normal.y;
     let band_frequency = 8.0;
     let band_position = (y_coord * band_frequency).sin();
     let band_noise = fbm(uv.x * 3.0, uv.y * 15.0, 3);
@@ -450,6 +626,14 @@ fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         band_color_4
     };
     
+    StaticLayers { color: base_bands, mask: 1.0 }
+}
+
+fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
+    let normal = fragment.normal.normalize();
+    let uv = get_uv_from_position(&fragment.normal);
+    let base_bands = static_layers_at(fragment, uniforms, jupiter_static_layers).color;
+    
     // CAPA 2: Turbulencia
     let turbulence_noise = fbm(uv.x * 10.0 + uniforms.time * 0.005, uv.y * 20.0, 5);
     let turbulence_light = Color::new(230, 200, 170);
@@ -507,20 +691,19 @@ fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     };
     
     // Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.30;
-    let intensity = ambient + diffuse * 0.70;
+    let light = light_at(fragment, &normal, uniforms);
+    let ambient = 0.07;
     
-    pole_darkening * intensity
+    apply_lighting(HdrColor::from(pole_darkening), ambient, light.diffuse * 0.70, light.color)
 }
 
 // ============================================
 // SHADER: SATURNO (Gigante Gaseoso) - 4 CAPAS
 // ============================================
-fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
-    let uv = get_uv_from_position(&fragment.normal);
+/// Capas estáticas de Saturno: color base y bandas (el hexágono polar va
+/// encima de la turbulencia, así que se sigue calculando por píxel)
+fn saturn_static_layers(normal: &Vec3) -> StaticLayers {
+    let uv = get_uv_from_position(normal);
     
     // CAPA 1: Color Base Crema/Dorado
     let base_cream = Color::new(230, 210, 180);
@@ -542,7 +725,7 @@ fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let band_medium = Color::new(220, 200, 170);
     let band_dark = Color::new(200, 175, 145);
     
-    let y_coord = fragment.normal.y;
+    let y_coord = normal.y;
     let band_frequency = 12.0;
     let band_position = (y_coord * band_frequency).sin();
     let band_noise = fbm(uv.x * 2.0, uv.y * 25.0, 2);
@@ -560,6 +743,14 @@ fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     
     let color_with_bands = mix_color(&base_color, &bands_color, 0.5);
     
+    StaticLayers { color: color_with_bands, mask: 1.0 }
+}
+
+fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
+    let normal = fragment.normal.normalize();
+    let uv = get_uv_from_position(&fragment.normal);
+    let color_with_bands = static_layers_at(fragment, uniforms, saturn_static_layers).color;
+    
     // CAPA 3: Turbulencia SUTIL
     let turbulence_light = Color::new(250, 230, 195);
     let turbulence_shadow = Color::new(195, 170, 140);
@@ -600,20 +791,18 @@ fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     };
     
     // Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.35;
-    let intensity = ambient + diffuse * 0.65;
+    let light = light_at(fragment, &normal, uniforms);
+    let ambient = 0.1;
     
-    color_with_hexagon * intensity
+    apply_lighting(HdrColor::from(color_with_hexagon), ambient, light.diffuse * 0.65, light.color)
 }
 
 // ============================================
 // SHADER: NEPTUNO (Gigante de Hielo) - 4 CAPAS
 // ============================================
-fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
-    let uv = get_uv_from_position(&fragment.normal);
+/// Neptuno no tiene capas animadas: todo menos la iluminación es estático
+fn neptune_static_layers(normal: &Vec3) -> StaticLayers {
+    let uv = get_uv_from_position(normal);
     
     // CAPA 1: Color Base Azul Intenso
     let base_color = Color::new(62, 84, 232);
@@ -632,27 +821,32 @@ fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let color_with_storms = mix_color(&base_color, &dark_blue, storm_factor * 0.4);
     
     // CAPA 3: Variación de Color con Latitud
-    let latitude = fragment.normal.y;
+    let latitude = normal.y;
     let latitude_factor = (1.0 - latitude.abs()) * 0.3;
     let lighter_blue = Color::new(100, 120, 255);
     
     let color_with_latitude = mix_color(&color_with_storms, &lighter_blue, latitude_factor);
     
+    StaticLayers { color: color_with_latitude, mask: 1.0 }
+}
+
+fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
+    let normal = fragment.normal.normalize();
+    let color_with_latitude = static_layers_at(fragment, uniforms, neptune_static_layers).color;
+    
     // CAPA 4: Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.3;
-    let intensity = ambient + diffuse * 0.7;
+    let light = light_at(fragment, &normal, uniforms);
+    let ambient = 0.07;
     
-    color_with_latitude * intensity
+    apply_lighting(HdrColor::from(color_with_latitude), ambient, light.diffuse * 0.7, light.color)
 }
 
 // ============================================
 // SHADER: LUNA (Satélite de la Tierra) - 3 CAPAS
 // ============================================
-fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
-    let uv = get_uv_from_position(&fragment.normal);
+/// La Luna no tiene capas animadas: todo menos la iluminación es estático
+fn moon_static_layers(normal: &Vec3) -> StaticLayers {
+    let uv = get_uv_from_position(normal);
     
     // CAPA 1: Color Base Gris
     let moon_gray = Color::new(180, 180, 180);
@@ -682,14 +876,14 @@ fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         let local_x = (uv.x * scale) - grid_x;
         let local_y = (uv.y * scale) - grid_y;
         
-        let cell_hash = hash_2d(grid_x as i32 * 127 + layer as i32 * 311, 
-                                 grid_y as i32 * 257 + layer as i32 * 419);
+        let cell_hash = hash_2d(grid_x as i32 * 127 + layer * 311, 
+                                 grid_y as i32 * 257 + layer * 419);
         
         let crater_probability = 0.35 - layer as f32 * 0.1;
         
         if cell_hash > (1.0 - crater_probability) {
-            let offset_hash_x = hash_2d(grid_x as i32 * 73, grid_y as i32 * 151 + layer as i32);
-            let offset_hash_y = hash_2d(grid_x as i32 * 179, grid_y as i32 * 283 + layer as i32);
+            let offset_hash_x = hash_2d(grid_x as i32 * 73, grid_y as i32 * 151 + layer);
+            let offset_hash_y = hash_2d(grid_x as i32 * 179, grid_y as i32 * 283 + layer);
             
             let crater_center_x = 0.3 + offset_hash_x * 0.4;
             let crater_center_y = 0.3 + offset_hash_y * 0.4;
@@ -720,25 +914,35 @@ fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         color_with_craters
     };
     
+    StaticLayers { color: color_with_maria, mask: 1.0 }
+}
+
+fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
+    let normal = fragment.normal.normalize();
+    let color_with_maria = static_layers_at(fragment, uniforms, moon_static_layers).color;
+    
     // Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.15;
-    let intensity = ambient + diffuse * 0.85;
+    let light = light_at(fragment, &normal, uniforms);
+    let ambient = 0.02;
     
-    color_with_maria * intensity
+    apply_lighting(HdrColor::from(color_with_maria), ambient, light.diffuse * 0.85, light.color)
 }
 
 // ============================================
 // SHADER: ANILLOS DE SATURNO - 3 CAPAS
 // ============================================
-fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
+fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
+    // El anillo se ilumina por las dos caras: la normal mira hacia la luz principal
+    let mut normal = fragment.normal.normalize();
+    if let Some(sun) = uniforms.lights.first() {
+        if normal.dot(&(sun.position - fragment.world_position)) < 0.0 {
+            normal = -normal;
+        }
+    }
+    let light = light_at(fragment, &normal, uniforms);
     
-    // Calcular distancia desde el centro
-    let pos = fragment.position;
-    let distance_from_center = (pos.x * pos.x + pos.y * pos.y).sqrt();
-    let normalized_dist = (distance_from_center - 400.0) / 400.0;
+    // Distancia radial: la coordenada U va de 0 (borde interno) a 1 (externo)
+    let normalized_dist = fragment.tex_coords.x;
     
     // CAPA 1: Bandas Concéntricas
     let band1 = Color::new(220, 200, 170);
@@ -762,64 +966,64 @@ fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let density_noise = simple_noise(normalized_dist * 50.0, 0.0);
     let color_with_density = mix_color(&base_color, &gap_color, density_noise * 0.3);
     
-    // CAPA 3: Transparencia Variable
-    let alpha = if normalized_dist > 0.65 && normalized_dist < 0.70 {
-        0.2
-    } else {
-        0.7 + density_noise * 0.2
-    };
+    // CAPA 3: Transparencia Variable (cobertura que se mezcla con el fondo)
+    let alpha = ring_opacity(normalized_dist);
     
-    let background = Color::new(10, 5, 20);
-    let final_color = mix_color(&background, &color_with_density, alpha);
-    
-    // Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).abs().max(0.3);
+    // Iluminación (la sombra del planeta cae sobre el anillo)
+    let ambient = 0.07;
+    let direct = (light.diffuse - ambient).max(0.0);
     
-    Color::new(
-        (final_color.r as f32 * diffuse) as u8,
-        (final_color.g as f32 * diffuse) as u8,
-        (final_color.b as f32 * diffuse) as u8,
-    )
+    let lit = apply_lighting(HdrColor::from(color_with_density), ambient, direct, light.color);
+    HdrColor { a: alpha, ..lit }
 }
 
-// ============================================
-// SHADER: ÓRBITAS
-// ============================================
-fn orbit_shader(_fragment: &Fragment, _uniforms: &Uniforms) -> Color {
-    // Color cyan semi-transparente para las órbitas
-    Color::new(70, 140, 200)
+/// Opacidad del anillo a una distancia radial normalizada (0 = borde interno,
+/// 1 = externo). La usan el shader y las sombras que proyecta el anillo.
+pub fn ring_opacity(normalized_dist: f32) -> f32 {
+    if normalized_dist > 0.65 && normalized_dist < 0.70 {
+        0.2
+    } else {
+        0.7 + simple_noise(normalized_dist * 50.0, 0.0) * 0.2
+    }
 }
 
 // ============================================
-// SHADER: SKYBOX CON ESTRELLAS PROCEDURALES
+// SHADER: SKYBOX (Vía Láctea)
 // ============================================
-fn skybox_shader(fragment: &Fragment, _uniforms: &Uniforms) -> Color {
-    let pos = &fragment.normal;
-    
-    // Base oscura
-    let base = Color::new(5, 5, 15);
-    
-    // Solo algunas estrellas simples
-    let star_hash = hash_2d(
-        (pos.x * 50.0) as i32,
-        (pos.z * 50.0) as i32
-    );
-    
-    if star_hash > 0.95 {
-        Color::new(255, 255, 255)
-    } else if star_hash > 0.93 {
-        Color::new(200, 200, 220)
-    } else {
-        base
+/// Resplandor de la Vía Láctea: una banda difusa alrededor del plano
+/// galáctico, con nubes y polvo de ruido. El skybox se dibuja con mezcla
+/// aditiva, así que el color de fondo (y su tinte durante el warp) queda debajo.
+fn skybox_shader(fragment: &Fragment, _uniforms: &Uniforms) -> HdrColor {
+    let direction = fragment.varyings.get_vec3(varyings::OBJECT_POSITION).normalize();
+
+    // Plano galáctico inclinado respecto a la eclíptica
+    let galactic_pole = Vec3::new(0.8, 0.6, 0.0);
+    let latitude = direction.dot(&galactic_pole);
+    let band = (-(latitude / 0.2).powi(2)).exp();
+    if band < 0.01 {
+        return HdrColor::black();
     }
+
+    // Ruido sobre el plano galáctico: sin costura y sin estirarse dentro de la banda
+    let east = galactic_pole.cross(&Vec3::new(1.0, 0.0, 0.0)).normalize();
+    let north = galactic_pole.cross(&east);
+    let u = direction.dot(&east) * 6.0;
+    let v = direction.dot(&north) * 6.0;
+
+    let clouds = fbm(u + 10.0, v + 10.0, 4);
+    let dust = smoothstep(0.5, 0.7, fbm(u * 2.5 + 40.0, v * 2.5 + 40.0, 3));
+    let glow = band * (0.3 + clouds) * (1.0 - dust * 0.7);
+
+    HdrColor::from(Color::new(170, 180, 220)) * (glow * 0.03)
 }
 
 // ============================================
 // SHADER: NAVE ESPACIAL
 // ============================================
-fn spaceship_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let light_dir = uniforms.light_dir.normalize();
+fn spaceship_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
+    let light = light_at(fragment, &normal, uniforms);
+    let light_dir = light.direction;
     
     // Color base metálico
     let base_color = Color::new(180, 190, 200);      // Gris metálico
@@ -827,10 +1031,10 @@ fn spaceship_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let highlight = Color::new(220, 230, 240);       // Brillo metálico
     
     // Iluminación difusa
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
+    let diffuse = light.diffuse;
     
     // Iluminación especular (brillo metálico)
-    let view_dir = Vec3::new(0.0, 0.0, 1.0);
+    let view_dir = view_direction(fragment, uniforms);
     let half_dir = (light_dir + view_dir).normalize();
     let spec = nalgebra_glm::dot(&normal, &half_dir).max(0.0).powf(32.0);
     
@@ -849,42 +1053,12 @@ fn spaceship_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     };
     
     // Agregar brillo especular
-    let final_color = if spec > 0.5 {
+    let color = if spec > 0.5 {
         mix_color(&lit_color, &highlight, spec * 0.6)
     } else {
         lit_color
     };
-    
-    final_color
-}
-
-// ============================================
-// SHADER: EFECTO WARP (Distorsión)
-// ============================================
-fn warp_effect_shader(fragment: &Fragment, uniforms: &Uniforms, distortion: f32) -> Color {
-    let base_color = fragment.color;
-    
-    if distortion < 0.1 {
-        return base_color;
-    }
-
-    // Efecto de líneas de velocidad
-    let speed_lines = (fragment.position.x * 0.1 + uniforms.time * 10.0).sin();
-    
-    if speed_lines > 0.9 - distortion {
-        // Líneas blancas/azules brillantes
-        let line_color = Color::new(150, 200, 255);
-        let blend_factor = distortion * 0.7;
-        mix_color(&base_color, &line_color, blend_factor)
-    } else {
-        // Desaturar y oscurecer durante el warp
-        let darkened = Color::new(
-            (base_color.r as f32 * (1.0 - distortion * 0.5)) as u8,
-            (base_color.g as f32 * (1.0 - distortion * 0.5)) as u8,
-            (base_color.b as f32 * (1.0 - distortion * 0.3)) as u8,
-        );
-        darkened
-    }
+    HdrColor::from(color)
 }
 
 // ============================================
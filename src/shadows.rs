@@ -0,0 +1,102 @@
+use nalgebra_glm::Vec3;
+use crate::shaders::{ring_opacity, smoothstep};
+
+/// Un punto a menos de esta fracción del radio de una esfera se considera
+/// parte de ella: la malla facetada queda dentro de la esfera analítica y
+/// sin este margen el cuerpo se haría sombra a sí mismo en el terminador
+const SELF_SHADOW_MARGIN: f32 = 1.05;
+
+/// Cuerpo esférico que puede tapar el sol
+#[derive(Clone, Copy, Debug)]
+pub struct SphereOccluder {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+/// Anillo plano (corona circular) que filtra la luz según su opacidad
+#[derive(Clone, Copy, Debug)]
+pub struct RingOccluder {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+}
+
+/// Oclusores de la luz de un frame. Las sombras se calculan de forma
+/// analítica por fragmento: se lanza un rayo hacia la luz y se intersecta con
+/// las esferas de los cuerpos y el plano de cada anillo. Una luz con tamaño
+/// (el sol) da eclipses con penumbra.
+#[derive(Clone, Debug, Default)]
+pub struct ShadowCasters {
+    pub spheres: Vec<SphereOccluder>,
+    pub rings: Vec<RingOccluder>,
+}
+
+impl ShadowCasters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fracción de la luz de una fuente esférica en `light_position` de radio
+    /// `light_radius` que llega a `point` (1 = sin sombra)
+    pub fn visibility(&self, point: &Vec3, light_position: &Vec3, light_radius: f32) -> f32 {
+        let to_light = light_position - point;
+        let light_distance = to_light.magnitude();
+        if light_distance <= light_radius {
+            return 1.0;
+        }
+
+        let direction = to_light / light_distance;
+        let light_angle = (light_radius / light_distance).asin();
+        let mut visibility = 1.0;
+
+        for sphere in &self.spheres {
+            let offset = sphere.center - point;
+            let distance = offset.magnitude();
+            let along = offset.dot(&direction);
+            if distance < sphere.radius * SELF_SHADOW_MARGIN || along <= 0.0 || distance >= light_distance {
+                continue;
+            }
+
+            let occluder_angle = (sphere.radius / distance).asin();
+            let separation = (along / distance).clamp(-1.0, 1.0).acos();
+            visibility *= 1.0 - disc_overlap(light_angle, occluder_angle, separation);
+        }
+
+        for ring in &self.rings {
+            let facing = ring.normal.dot(&direction);
+            if facing.abs() < 1e-6 {
+                continue;
+            }
+
+            // Los fragmentos del propio anillo quedan en t ≈ 0
+            let t = ring.normal.dot(&(ring.center - point)) / facing;
+            if t <= ring.outer_radius * 1e-3 || t >= light_distance {
+                continue;
+            }
+
+            let radius = (point + direction * t - ring.center).magnitude();
+            if radius > ring.inner_radius && radius < ring.outer_radius {
+                let normalized = (radius - ring.inner_radius) / (ring.outer_radius - ring.inner_radius);
+                visibility *= 1.0 - ring_opacity(normalized);
+            }
+        }
+
+        visibility
+    }
+}
+
+/// Fracción aproximada del disco de la luz (radio angular `light`) tapada por un
+/// disco de radio angular `occluder` cuyos centros están a `separation`
+fn disc_overlap(light: f32, occluder: f32, separation: f32) -> f32 {
+    // Luz puntual: sombra dura
+    if light <= 1e-6 {
+        return if separation < occluder { 1.0 } else { 0.0 };
+    }
+
+    let full = (occluder / light).powi(2).min(1.0);
+    let inner = (occluder - light).abs();
+    let outer = occluder + light;
+
+    full * (1.0 - smoothstep(inner, outer, separation))
+}
@@ -6,7 +6,6 @@ pub struct Spaceship {
     pub model: Model,
     pub offset: Vec3,          // Offset relativo a la cámara
     pub scale: f32,
-    pub rotation: Vec3,
 }
 
 impl Spaceship {
@@ -15,7 +14,6 @@ impl Spaceship {
             model,
             offset: Vec3::new(0.0, -0.8, -2.5), 
             scale: 0.08, 
-            rotation: Vec3::zeros(),
         }
     }
 
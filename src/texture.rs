@@ -0,0 +1,503 @@
+use crate::color::{srgb_to_linear, HdrColor};
+use nalgebra_glm::Vec2;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Cómo se combinan los texels vecinos al muestrear
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    /// El texel más cercano del nivel 0
+    Nearest,
+    /// Interpolación de los cuatro texels más cercanos del nivel 0
+    Bilinear,
+    /// Bilineal en los dos niveles de mip más cercanos al `lod`, mezclados
+    Trilinear,
+}
+
+impl Filter {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "nearest" => Some(Filter::Nearest),
+            "bilinear" => Some(Filter::Bilinear),
+            "trilinear" => Some(Filter::Trilinear),
+            _ => None,
+        }
+    }
+}
+
+/// Qué pasa con las coordenadas fuera de [0, 1]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Wrap {
+    /// La textura se repite (la longitud de un mapa equirectangular)
+    Repeat,
+    /// Se usa el texel del borde (la latitud, para no mezclar los polos)
+    Clamp,
+}
+
+impl Wrap {
+    fn apply(self, index: i64, size: usize) -> usize {
+        match self {
+            Wrap::Repeat => index.rem_euclid(size as i64) as usize,
+            Wrap::Clamp => index.clamp(0, size as i64 - 1) as usize,
+        }
+    }
+}
+
+/// Filtro y modo de borde de cada eje
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sampler {
+    pub filter: Filter,
+    pub wrap_u: Wrap,
+    pub wrap_v: Wrap,
+}
+
+impl Sampler {
+    /// Para mapas equirectangulares: repite en longitud y recorta en latitud
+    pub fn equirectangular(filter: Filter) -> Self {
+        Sampler { filter, wrap_u: Wrap::Repeat, wrap_v: Wrap::Clamp }
+    }
+}
+
+/// Cómo se interpretan los bytes de una imagen al cargarla
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    /// Colores en sRGB: se decodifican a lineal (fotos, mapas de superficie)
+    Srgb,
+    /// Datos: cada byte / 255 tal cual (máscaras, mapas horneados en el
+    /// espacio en que trabajan los shaders)
+    Raw,
+}
+
+/// Un nivel de mip, fila 0 arriba
+struct MipLevel {
+    width: usize,
+    height: usize,
+    texels: Vec<HdrColor>,
+}
+
+impl MipLevel {
+    fn texel(&self, x: i64, y: i64, sampler: &Sampler) -> HdrColor {
+        let x = sampler.wrap_u.apply(x, self.width);
+        let y = sampler.wrap_v.apply(y, self.height);
+        self.texels[y * self.width + x]
+    }
+
+    fn nearest(&self, uv: Vec2, sampler: &Sampler) -> HdrColor {
+        let x = (uv.x * self.width as f32).floor() as i64;
+        let y = (uv.y * self.height as f32).floor() as i64;
+        self.texel(x, y, sampler)
+    }
+
+    /// Los centros de los texels están en (i + 0.5) / tamaño
+    fn bilinear(&self, uv: Vec2, sampler: &Sampler) -> HdrColor {
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = uv.y * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(x0, y0, sampler).lerp(&self.texel(x0 + 1, y0, sampler), fx);
+        let bottom = self.texel(x0, y0 + 1, sampler).lerp(&self.texel(x0 + 1, y0 + 1, sampler), fx);
+        top.lerp(&bottom, fy)
+    }
+
+    /// Nivel siguiente a la mitad de tamaño, promediando bloques de 2×2
+    fn downsample(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut texels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = HdrColor { r: 0.0, g: 0.0, b: 0.0, a: 0.0 };
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sx = (x * 2 + dx).min(self.width - 1);
+                    let sy = (y * 2 + dy).min(self.height - 1);
+                    let texel = self.texels[sy * self.width + sx];
+                    sum = HdrColor {
+                        r: sum.r + texel.r,
+                        g: sum.g + texel.g,
+                        b: sum.b + texel.b,
+                        a: sum.a + texel.a,
+                    };
+                }
+                texels.push(HdrColor { r: sum.r * 0.25, g: sum.g * 0.25, b: sum.b * 0.25, a: sum.a * 0.25 });
+            }
+        }
+
+        MipLevel { width, height, texels }
+    }
+}
+
+/// Imagen en punto flotante con su cadena de mipmaps (hasta 1×1), generada al
+/// crearla. Se carga de PNG, PPM (P3/P5/P6) o TGA (sin comprimir o RLE).
+pub struct Texture {
+    levels: Vec<MipLevel>,
+}
+
+impl Texture {
+    /// Crea la textura a partir de texels lineales (fila 0 arriba)
+    pub fn from_texels(width: usize, height: usize, texels: Vec<HdrColor>) -> Result<Self, String> {
+        if width == 0 || height == 0 || texels.len() != width * height {
+            return Err(format!("Textura inválida: {}x{} con {} texels", width, height, texels.len()));
+        }
+
+        let mut levels = vec![MipLevel { width, height, texels }];
+        while let Some(last) = levels.last().filter(|level| level.width > 1 || level.height > 1) {
+            let next = last.downsample();
+            levels.push(next);
+        }
+
+        Ok(Texture { levels })
+    }
+
+    /// Crea la textura a partir de bytes RGBA (como los de una imagen); el
+    /// alfa nunca se decodifica
+    pub fn from_rgba8(width: usize, height: usize, rgba: &[u8], encoding: Encoding) -> Result<Self, String> {
+        let decode = |value: u8| match encoding {
+            Encoding::Srgb => srgb_to_linear(value),
+            Encoding::Raw => value as f32 / 255.0,
+        };
+        let texels = rgba
+            .chunks_exact(4)
+            .map(|p| HdrColor { r: decode(p[0]), g: decode(p[1]), b: decode(p[2]), a: p[3] as f32 / 255.0 })
+            .collect();
+        Self::from_texels(width, height, texels)
+    }
+
+    /// Carga una imagen según su extensión: .png, .ppm/.pgm o .tga
+    pub fn load(path: &Path, encoding: Encoding) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        let decode = match extension.as_str() {
+            "png" => decode_png,
+            "ppm" | "pgm" => decode_ppm,
+            "tga" => decode_tga,
+            _ => return Err(format!("Formato de textura no soportado: {}", path.display())),
+        };
+        let data = std::fs::read(path).map_err(|e| format!("No se pudo abrir {}: {}", path.display(), e))?;
+        let (width, height, rgba) = decode(&data).map_err(|e| format!("Error leyendo {}: {}", path.display(), e))?;
+
+        Self::from_rgba8(width, height, &rgba, encoding)
+    }
+
+    pub fn width(&self) -> usize {
+        self.levels[0].width
+    }
+
+    pub fn height(&self) -> usize {
+        self.levels[0].height
+    }
+
+    pub fn mip_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Muestrea en `uv` (0,0 arriba a la izquierda). `lod` es el log2 de
+    /// cuántos texels del nivel 0 caen en un píxel; solo lo usa el trilineal.
+    pub fn sample(&self, uv: Vec2, sampler: &Sampler, lod: f32) -> HdrColor {
+        match sampler.filter {
+            Filter::Nearest => self.levels[0].nearest(uv, sampler),
+            Filter::Bilinear => self.levels[0].bilinear(uv, sampler),
+            Filter::Trilinear => {
+                let lod = lod.clamp(0.0, (self.levels.len() - 1) as f32);
+                let level = lod.floor() as usize;
+                let fine = self.levels[level].bilinear(uv, sampler);
+                if level + 1 >= self.levels.len() {
+                    return fine;
+                }
+                let coarse = self.levels[level + 1].bilinear(uv, sampler);
+                fine.lerp(&coarse, lod - level as f32)
+            }
+        }
+    }
+}
+
+/// Mapa de superficie equirectangular de un cuerpo. `blend` es cuánto
+/// reemplaza al shader procedural (1 = solo la textura, 0.5 = mitad y mitad);
+/// el alfa de cada texel lo atenúa, así un mapa puede cubrir solo una parte.
+#[derive(Clone)]
+pub struct SurfaceMap {
+    pub texture: Arc<Texture>,
+    pub sampler: Sampler,
+    pub blend: f32,
+}
+
+impl SurfaceMap {
+    pub fn new(texture: Arc<Texture>, blend: f32) -> Self {
+        SurfaceMap {
+            texture,
+            sampler: Sampler::equirectangular(Filter::Trilinear),
+            blend: blend.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.sampler.filter = filter;
+        self
+    }
+}
+
+/// Ancho, alto y bytes RGBA de una imagen decodificada
+type DecodedImage = (usize, usize, Vec<u8>);
+
+fn decode_png(data: &[u8]) -> Result<DecodedImage, String> {
+    let mut decoder = png::Decoder::new(data);
+    // Paleta y escala de grises a 8 bits por canal, 16 bits a 8
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+    let bytes = &buffer[..info.buffer_size()];
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => bytes.to_vec(),
+        png::ColorType::Rgb => bytes.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => bytes.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale | png::ColorType::Indexed => bytes.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+    };
+    Ok((info.width as usize, info.height as usize, rgba))
+}
+
+/// PPM/PGM en texto (P2/P3) o binario (P5/P6), de hasta 16 bits por canal
+fn decode_ppm(data: &[u8]) -> Result<DecodedImage, String> {
+    let mut position = 0;
+    let mut token = || -> Option<String> {
+        // Saltar espacios y comentarios hasta el siguiente token
+        loop {
+            while position < data.len() && data[position].is_ascii_whitespace() {
+                position += 1;
+            }
+            if position < data.len() && data[position] == b'#' {
+                while position < data.len() && data[position] != b'\n' {
+                    position += 1;
+                }
+                continue;
+            }
+            break;
+        }
+        let start = position;
+        while position < data.len() && !data[position].is_ascii_whitespace() {
+            position += 1;
+        }
+        (start < position).then(|| String::from_utf8_lossy(&data[start..position]).into_owned())
+    };
+
+    let magic = token().ok_or("archivo vacío")?;
+    let channels = match magic.as_str() {
+        "P2" | "P5" => 1,
+        "P3" | "P6" => 3,
+        _ => return Err(format!("cabecera PPM desconocida: {}", magic)),
+    };
+    let mut number = |name: &str| -> Result<usize, String> {
+        token()
+            .and_then(|t| t.parse().ok())
+            .ok_or_else(|| format!("{} inválido en la cabecera", name))
+    };
+    let width = number("ancho")?;
+    let height = number("alto")?;
+    let max_value = number("valor máximo")?.clamp(1, 65535);
+    // Una cabecera corrupta puede pedir más valores de los que caben en `usize`
+    let count = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(channels))
+        .ok_or_else(|| format!("imagen demasiado grande: {}x{}", width, height))?;
+
+    let values: Vec<usize> = if magic == "P5" || magic == "P6" {
+        // Un solo espacio separa la cabecera de los datos
+        let start = position + 1;
+        let bytes_per_value = if max_value > 255 { 2 } else { 1 };
+        let end = count
+            .checked_mul(bytes_per_value)
+            .and_then(|bytes| bytes.checked_add(start))
+            .ok_or("datos incompletos")?;
+        let raw = data.get(start..end).ok_or("datos incompletos")?;
+        if bytes_per_value == 2 {
+            raw.chunks_exact(2).map(|b| (b[0] as usize) << 8 | b[1] as usize).collect()
+        } else {
+            raw.iter().map(|&b| b as usize).collect()
+        }
+    } else {
+        let text = String::from_utf8_lossy(&data[position..]);
+        let values: Vec<usize> = text
+            .split_ascii_whitespace()
+            .take(count)
+            .map(|t| t.parse().map_err(|_| format!("valor inválido: {}", t)))
+            .collect::<Result<_, _>>()?;
+        if values.len() < count {
+            return Err("datos incompletos".to_string());
+        }
+        values
+    };
+
+    let to_byte = |value: usize| (value.min(max_value) * 255 / max_value) as u8;
+    let rgba = values
+        .chunks_exact(channels)
+        .flat_map(|p| {
+            let (r, g, b) = if channels == 1 { (p[0], p[0], p[0]) } else { (p[0], p[1], p[2]) };
+            [to_byte(r), to_byte(g), to_byte(b), 255]
+        })
+        .collect();
+    Ok((width, height, rgba))
+}
+
+/// TGA color verdadero (tipos 2 y 10) o escala de grises (3 y 11), de 8, 24
+/// o 32 bits, sin comprimir o con RLE
+fn decode_tga(data: &[u8]) -> Result<DecodedImage, String> {
+    let header = data.get(..18).ok_or("cabecera TGA incompleta")?;
+    let id_length = header[0] as usize;
+    let colormap_type = header[1];
+    let image_type = header[2];
+    let colormap_length = u16::from_le_bytes([header[5], header[6]]) as usize;
+    let colormap_depth = header[7] as usize;
+    let width = u16::from_le_bytes([header[12], header[13]]) as usize;
+    let height = u16::from_le_bytes([header[14], header[15]]) as usize;
+    let depth = header[16] as usize;
+    let top_to_bottom = header[17] & 0x20 != 0;
+
+    let (grayscale, rle) = match image_type {
+        2 => (false, false),
+        3 => (true, false),
+        10 => (false, true),
+        11 => (true, true),
+        _ => return Err(format!("tipo de TGA no soportado: {}", image_type)),
+    };
+    let bytes_per_pixel = match (grayscale, depth) {
+        (true, 8) => 1,
+        (false, 24) => 3,
+        (false, 32) => 4,
+        _ => return Err(format!("profundidad de TGA no soportada: {} bits", depth)),
+    };
+
+    // Se saltan el id y la paleta (que las imágenes de color verdadero no usan)
+    let colormap_bytes = if colormap_type == 1 { colormap_length * colormap_depth.div_ceil(8) } else { 0 };
+    let mut position = 18 + id_length + colormap_bytes;
+    // Ancho y alto son u16: los píxeles entran en `usize`, pero sus bytes RGBA
+    // pueden no entrar en un destino de 32 bits
+    let pixel_count = width * height;
+    if pixel_count.checked_mul(4).is_none() {
+        return Err(format!("imagen demasiado grande: {}x{}", width, height));
+    }
+    let image_bytes = pixel_count * bytes_per_pixel;
+
+    // Nada se reserva según la cabecera antes de ver que el archivo trae los
+    // datos: sin comprimir se exigen los bytes completos y con RLE el vector
+    // crece paquete a paquete
+    let mut decoded = Vec::new();
+    let pixels: &[u8] = if rle {
+        while decoded.len() < image_bytes {
+            let packet = *data.get(position).ok_or("datos RLE incompletos")?;
+            position += 1;
+            let run = (packet & 0x7F) as usize + 1;
+            if packet & 0x80 != 0 {
+                let pixel = data.get(position..position + bytes_per_pixel).ok_or("datos RLE incompletos")?;
+                position += bytes_per_pixel;
+                for _ in 0..run {
+                    decoded.extend_from_slice(pixel);
+                }
+            } else {
+                let raw = data.get(position..position + run * bytes_per_pixel).ok_or("datos RLE incompletos")?;
+                position += run * bytes_per_pixel;
+                decoded.extend_from_slice(raw);
+            }
+        }
+        decoded.truncate(image_bytes);
+        &decoded
+    } else {
+        data.get(position..position + image_bytes).ok_or("datos incompletos")?
+    };
+
+    // Los canales vienen como BGR(A) y las filas de abajo hacia arriba salvo el bit 5
+    let mut rgba = vec![0; pixel_count * 4];
+    for y in 0..height {
+        let source_row = if top_to_bottom { y } else { height - 1 - y };
+        for x in 0..width {
+            let p = &pixels[(source_row * width + x) * bytes_per_pixel..][..bytes_per_pixel];
+            let color = match bytes_per_pixel {
+                1 => [p[0], p[0], p[0], 255],
+                3 => [p[2], p[1], p[0], 255],
+                _ => [p[2], p[1], p[0], p[3]],
+            };
+            rgba[(y * width + x) * 4..][..4].copy_from_slice(&color);
+        }
+    }
+
+    Ok((width, height, rgba))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ppm_header_that_overflows_is_an_error() {
+        let huge = format!("P6 {} {} 255\n", usize::MAX / 2, 3);
+        assert!(decode_ppm(huge.as_bytes()).unwrap_err().contains("demasiado grande"));
+
+        // El tamaño entra en `usize` pero no sus bytes de 16 bits
+        let wide = format!("P5 {} 1 65535\n", usize::MAX / 2 + 1);
+        assert!(decode_ppm(wide.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn ppm_decodes_binary_and_ascii() {
+        let binary = b"P6 2 1 255\n\xff\x00\x00\x00\x80\xff";
+        assert_eq!(decode_ppm(binary).unwrap(), (2, 1, vec![255, 0, 0, 255, 0, 128, 255, 255]));
+
+        let ascii = b"P2\n# gris\n2 1\n15\n0 15\n";
+        assert_eq!(decode_ppm(ascii).unwrap(), (2, 1, vec![0, 0, 0, 255, 255, 255, 255, 255]));
+    }
+
+    /// Cabecera TGA de color verdadero de 24 bits seguida de `body`
+    fn tga(image_type: u8, width: u16, height: u16, top_to_bottom: bool, body: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; 18];
+        data[2] = image_type;
+        data[12..14].copy_from_slice(&width.to_le_bytes());
+        data[14..16].copy_from_slice(&height.to_le_bytes());
+        data[16] = 24;
+        data[17] = if top_to_bottom { 0x20 } else { 0 };
+        data.extend_from_slice(body);
+        data
+    }
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+    #[test]
+    fn tga_decodes_uncompressed_rows_in_both_orders() {
+        // Filas en BGR: rojo, verde / azul, blanco
+        let rows = [0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255];
+        let top_down = decode_tga(&tga(2, 2, 2, true, &rows)).unwrap();
+        assert_eq!(top_down, (2, 2, [RED, GREEN, BLUE, WHITE].concat()));
+
+        // Sin el bit 5 la primera fila del archivo es la de abajo
+        let bottom_up = decode_tga(&tga(2, 2, 2, false, &rows)).unwrap();
+        assert_eq!(bottom_up, (2, 2, [BLUE, WHITE, RED, GREEN].concat()));
+    }
+
+    #[test]
+    fn tga_decodes_rle_packets() {
+        // Repetición de 2 píxeles rojos y un paquete crudo con uno azul
+        let body = [0x81, 0, 0, 255, 0x00, 255, 0, 0];
+        let decoded = decode_tga(&tga(10, 3, 1, true, &body)).unwrap();
+        assert_eq!(decoded, (3, 1, [RED, RED, BLUE].concat()));
+    }
+
+    #[test]
+    fn truncated_tga_is_an_error() {
+        let rows = [0, 0, 255, 0, 255, 0, 255, 0, 0];
+        assert_eq!(decode_tga(&tga(2, 2, 2, true, &rows)).unwrap_err(), "datos incompletos");
+        assert!(decode_tga(&tga(10, 2, 2, true, &[0x81, 0, 0, 255])).is_err());
+        assert!(decode_tga(&[0; 10]).is_err());
+
+        // Una cabecera de 65535×65535 sin datos falla sin reservar 16 GB
+        assert!(decode_tga(&tga(2, u16::MAX, u16::MAX, false, &[0; 2])).is_err());
+        assert!(decode_tga(&tga(10, u16::MAX, u16::MAX, false, &[0xFF, 1, 2, 3])).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::color::HdrColor;
+use crate::framebuffer::Framebuffer;
+use crate::triangle::RasterSample;
+
+/// Lado de cada tile en píxeles
+pub const TILE_SIZE: usize = 64;
+
+/// Región rectangular de la pantalla con sus propios buffers de color (HDR) y
+/// profundidad. Cada hilo trabaja sobre tiles distintos, sin compartir memoria.
+/// Con MSAA cada píxel guarda `samples` colores y profundidades consecutivos,
+/// que se promedian al escribir el tile de vuelta.
+pub struct Tile {
+    pub x0: usize,
+    pub y0: usize,
+    pub width: usize,
+    pub height: usize,
+    pub samples: usize,
+    pub color: Vec<HdrColor>,
+    pub depth: Vec<f32>,
+    /// Fragmentos rasterizados en cada píxel (para la vista de overdraw)
+    pub overdraw: Vec<u32>,
+}
+
+impl Tile {
+    /// Copia la región del framebuffer al tile, replicando cada píxel en todas sus muestras
+    pub fn load(framebuffer: &Framebuffer, x0: usize, y0: usize, samples: usize) -> Self {
+        let width = TILE_SIZE.min(framebuffer.width - x0);
+        let height = TILE_SIZE.min(framebuffer.height - y0);
+
+        let mut color = Vec::with_capacity(width * height * samples);
+        let mut depth = Vec::with_capacity(width * height * samples);
+        for y in y0..y0 + height {
+            let row = y * framebuffer.width;
+            for x in x0..x0 + width {
+                for _ in 0..samples {
+                    color.push(framebuffer.hdr[row + x]);
+                    depth.push(framebuffer.zbuffer[row + x]);
+                }
+            }
+        }
+
+        let overdraw = vec![0; width * height];
+        Tile { x0, y0, width, height, samples, color, depth, overdraw }
+    }
+
+    /// Escribe el contenido del tile de vuelta al framebuffer (resolviendo las
+    /// muestras: promedio de color y la profundidad más cercana)
+    pub fn store(&self, framebuffer: &mut Framebuffer) {
+        let weight = 1.0 / self.samples as f32;
+
+        for local_y in 0..self.height {
+            let row = (self.y0 + local_y) * framebuffer.width + self.x0;
+            for local_x in 0..self.width {
+                let first = (local_y * self.width + local_x) * self.samples;
+                let samples = first..first + self.samples;
+
+                let (color, depth) = if self.samples == 1 {
+                    (self.color[first], self.depth[first])
+                } else {
+                    let color = self.color[samples.clone()]
+                        .iter()
+                        .fold(HdrColor::new(0.0, 0.0, 0.0), |sum, c| sum + *c);
+                    let depth = self.depth[samples].iter().fold(f32::INFINITY, |a, b| a.min(*b));
+                    (color * weight, depth)
+                };
+
+                framebuffer.hdr[row + local_x] = color;
+                framebuffer.zbuffer[row + local_x] = depth;
+            }
+        }
+    }
+
+    /// Suma un fragmento rasterizado al contador de overdraw del píxel
+    pub fn count_fragment(&mut self, x: i32, y: i32) {
+        let pixel = self.index(x as usize, y as usize);
+        self.overdraw[pixel] += 1;
+    }
+
+    /// Reemplaza el color de los píxeles con algún fragmento por el que
+    /// `palette` asigna a su cantidad de fragmentos
+    pub fn show_overdraw(&mut self, palette: impl Fn(u32) -> HdrColor) {
+        for pixel in 0..self.overdraw.len() {
+            let count = self.overdraw[pixel];
+            if count > 0 {
+                let first = pixel * self.samples;
+                self.color[first..first + self.samples].fill(palette(count));
+            }
+        }
+    }
+
+    /// Rectángulo inclusivo en coordenadas de pantalla
+    pub fn rect(&self) -> (i32, i32, i32, i32) {
+        (
+            self.x0 as i32,
+            self.y0 as i32,
+            (self.x0 + self.width) as i32 - 1,
+            (self.y0 + self.height) as i32 - 1,
+        )
+    }
+
+    /// Índice local de un píxel de pantalla dentro del tile
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        (y - self.y0) * self.width + (x - self.x0)
+    }
+
+    /// Índice en `color`/`depth` de una muestra de un píxel local
+    pub fn sample_index(&self, pixel: usize, sample: usize) -> usize {
+        pixel * self.samples + sample
+    }
+}
+
+/// Triángulo de un frame: índice de la llamada de dibujo y del triángulo en ella
+pub type TriangleId = (usize, usize);
+
+/// G-buffer de visibilidad de un tile: por muestra guarda qué triángulo de
+/// qué llamada quedó más cerca entre todos los opacos del frame y sus pesos
+/// baricéntricos. Los atributos se interpolan y el fragment shader se ejecuta
+/// una sola vez por triángulo visible en cada píxel al resolverlo (una vez por
+/// píxel salvo en los bordes con MSAA). Cada hilo reutiliza el suyo entre tiles.
+pub struct GBuffer {
+    samples: usize,
+    entries: Vec<Option<(TriangleId, RasterSample)>>,
+    touched: Vec<usize>,
+}
+
+impl GBuffer {
+    pub fn new(samples: usize) -> Self {
+        GBuffer {
+            samples,
+            entries: vec![None; TILE_SIZE * TILE_SIZE * samples],
+            touched: Vec::new(),
+        }
+    }
+
+    /// Guarda la muestra visible `sample` del píxel `pixel`, reemplazando la anterior
+    pub fn write(&mut self, pixel: usize, triangle: TriangleId, sample: RasterSample) {
+        let first = pixel * self.samples;
+        if self.entries[first..first + self.samples].iter().all(Option::is_none) {
+            self.touched.push(pixel);
+        }
+        self.entries[first + sample.sample] = Some((triangle, sample));
+    }
+
+    /// Recorre los píxeles escritos entregando sus muestras (índice local del
+    /// píxel y una entrada por muestra) y deja el buffer vacío
+    pub fn drain<F: FnMut(usize, &mut [Option<(TriangleId, RasterSample)>])>(&mut self, mut visit: F) {
+        for pixel in self.touched.drain(..) {
+            let first = pixel * self.samples;
+            let entries = &mut self.entries[first..first + self.samples];
+            visit(pixel, entries);
+            entries.fill(None);
+        }
+    }
+}
+
+impl Default for GBuffer {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
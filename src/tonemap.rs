@@ -0,0 +1,80 @@
+use crate::color::{linear_to_srgb, Color, HdrColor};
+
+/// Operador que comprime el rango HDR al rango del monitor
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMapping {
+    /// Recorta en 1.0 (el comportamiento de antes del buffer HDR)
+    Clamp,
+    /// `x / (1 + x)` por canal
+    Reinhard,
+    /// Aproximación de la curva fílmica ACES (Narkowicz)
+    AcesFilmic,
+}
+
+impl ToneMapping {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" | "clamp" => Some(ToneMapping::Clamp),
+            "reinhard" => Some(ToneMapping::Reinhard),
+            "aces" => Some(ToneMapping::AcesFilmic),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToneMapping::Clamp => "sin tone mapping",
+            ToneMapping::Reinhard => "Reinhard",
+            ToneMapping::AcesFilmic => "ACES",
+        }
+    }
+
+    /// Siguiente operador (para alternarlos con una tecla)
+    pub fn next(self) -> Self {
+        match self {
+            ToneMapping::Clamp => ToneMapping::Reinhard,
+            ToneMapping::Reinhard => ToneMapping::AcesFilmic,
+            ToneMapping::AcesFilmic => ToneMapping::Clamp,
+        }
+    }
+
+    fn apply(self, x: f32) -> f32 {
+        match self {
+            ToneMapping::Clamp => x,
+            ToneMapping::Reinhard => x / (1.0 + x),
+            ToneMapping::AcesFilmic => {
+                (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+            }
+        }
+    }
+}
+
+/// Convierte el buffer lineal en colores de pantalla: exposición, curva y
+/// codificación sRGB
+#[derive(Clone, Copy, Debug)]
+pub struct ToneMapper {
+    pub operator: ToneMapping,
+    pub exposure: f32,
+}
+
+impl ToneMapper {
+    pub fn new(operator: ToneMapping, exposure: f32) -> Self {
+        ToneMapper { operator, exposure }
+    }
+
+    pub fn map(&self, color: HdrColor) -> Color {
+        let channel = |value: f32| linear_to_srgb(self.operator.apply(value.max(0.0) * self.exposure));
+        Color::new(channel(color.r), channel(color.g), channel(color.b))
+    }
+
+    /// Multiplica la exposición por `factor` (en pasos de cámara)
+    pub fn adjust_exposure(&mut self, factor: f32) {
+        self.exposure = (self.exposure * factor).clamp(0.05, 20.0);
+    }
+}
+
+impl Default for ToneMapper {
+    fn default() -> Self {
+        ToneMapper::new(ToneMapping::AcesFilmic, 1.0)
+    }
+}
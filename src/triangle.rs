@@ -1,5 +1,6 @@
-use crate::framebuffer::Framebuffer;
+use nalgebra_glm::Vec2;
 use crate::fragment::Fragment;
+use crate::varyings::Varyings;
 use crate::vertex::Vertex;
 
 pub struct Triangle {
@@ -13,77 +14,219 @@ impl Triangle {
         Triangle { v1, v2, v3 }
     }
 
-    pub fn draw(&self, framebuffer: &mut Framebuffer) -> Vec<Fragment> {
-        let mut fragments = Vec::new();
-
-        let v1_pos = self.v1.transformed_position;
-        let v2_pos = self.v2.transformed_position;
-        let v3_pos = self.v3.transformed_position;
-
-        let (x1, y1, z1) = (v1_pos.x as i32, v1_pos.y as i32, v1_pos.z);
-        let (x2, y2, z2) = (v2_pos.x as i32, v2_pos.y as i32, v2_pos.z);
-        let (x3, y3, z3) = (v3_pos.x as i32, v3_pos.y as i32, v3_pos.z);
+    /// Distancia media a la cámara (la `w` de clip es la profundidad en vista).
+    /// Se usa para ordenar la geometría transparente de atrás hacia adelante.
+    pub fn view_depth(&self) -> f32 {
+        (self.v1.clip_position.w + self.v2.clip_position.w + self.v3.clip_position.w) / 3.0
+    }
 
-        let min_x = x1.min(x2).min(x3).max(0);
-        let max_x = x1.max(x2).max(x3).min(framebuffer.width as i32 - 1);
-        let min_y = y1.min(y2).min(y3).max(0);
-        let max_y = y1.max(y2).max(y3).min(framebuffer.height as i32 - 1);
+    /// Caja envolvente en píxeles (min_x, min_y, max_x, max_y), sin recortar.
+    /// Incluye todo píxel que tenga alguna posición de muestra dentro del triángulo.
+    pub fn bounding_box(&self) -> (i32, i32, i32, i32) {
+        let p1 = self.v1.transformed_position;
+        let p2 = self.v2.transformed_position;
+        let p3 = self.v3.transformed_position;
+
+        (
+            p1.x.min(p2.x).min(p3.x).floor() as i32,
+            p1.y.min(p2.y).min(p3.y).floor() as i32,
+            p1.x.max(p2.x).max(p3.x).floor() as i32,
+            p1.y.max(p2.y).max(p3.y).floor() as i32,
+        )
+    }
 
-        let _edge1 = v2_pos - v1_pos;
-        let _edge2 = v3_pos - v1_pos;
-        //let triangle_normal = nalgebra_glm::cross(&edge1, &edge2).normalize();
+    /// Recorre las muestras cubiertas dentro de `rect` entregando solo posición,
+    /// profundidad y pesos baricéntricos. `pattern` son las posiciones de muestra
+    /// dentro de cada píxel; las de un mismo píxel se entregan seguidas.
+    /// Los varyings se interpolan después con `fragment`, solo para las muestras
+    /// que pasen el test de profundidad.
+    pub fn rasterize<F: FnMut(RasterSample)>(
+        &self,
+        rect: (i32, i32, i32, i32),
+        pattern: &[(f32, f32)],
+        mut emit: F,
+    ) {
+        let p1 = self.v1.transformed_position.xy();
+        let p2 = self.v2.transformed_position.xy();
+        let p3 = self.v3.transformed_position.xy();
+        let (z1, z2, z3) = (self.v1.transformed_position.z, self.v2.transformed_position.z, self.v3.transformed_position.z);
+
+        // Doble del área con signo; su signo indica de qué lado de cada arista queda el interior
+        let area = (p2 - p1).perp(&(p3 - p1));
+        if area.abs() < 1e-10 {
+            return;
+        }
+        let orientation = area.signum();
+        let inv_area = 1.0 / area.abs();
+
+        // Cada peso es la arista opuesta a su vértice
+        let edges = [
+            Edge::new(p2, p3, orientation),
+            Edge::new(p3, p1, orientation),
+            Edge::new(p1, p2, orientation),
+        ];
+
+        let (box_min_x, box_min_y, box_max_x, box_max_y) = self.bounding_box();
+        let min_x = box_min_x.max(rect.0);
+        let min_y = box_min_y.max(rect.1);
+        let max_x = box_max_x.min(rect.2);
+        let max_y = box_max_y.min(rect.3);
 
         for y in min_y..=max_y {
             for x in min_x..=max_x {
-                let (w1, w2, w3) = barycentric_coordinates(
-                    x as f32, y as f32,
-                    x1 as f32, y1 as f32,
-                    x2 as f32, y2 as f32,
-                    x3 as f32, y3 as f32,
-                );
-
-                if w1 >= 0.0 && w2 >= 0.0 && w3 >= 0.0 {
-                    let depth = z1 * w1 + z2 * w2 + z3 * w3;
-                    
-                    let normal = (self.v1.transformed_normal * w1 + 
-                                 self.v2.transformed_normal * w2 + 
-                                 self.v3.transformed_normal * w3).normalize();
-
-                    let intensity = 1.0;
-
-                    let fragment = Fragment::new_with_normal(
-                        x as f32,
-                        y as f32,
-                        framebuffer.current_color,
-                        depth,
-                        normal,
-                        intensity,
-                    );
-
-                    fragments.push(fragment);
+                for (index, &(offset_x, offset_y)) in pattern.iter().enumerate() {
+                    let sample = Vec2::new(x as f32 + offset_x, y as f32 + offset_y);
+                    let [e1, e2, e3] = edges.map(|edge| edge.evaluate(sample));
+
+                    if edges[0].covers(e1) && edges[1].covers(e2) && edges[2].covers(e3) {
+                        let (w1, w2, w3) = (e1 * inv_area, e2 * inv_area, e3 * inv_area);
+                        // La profundidad de pantalla es afín en espacio de pantalla
+                        let depth = z1 * w1 + z2 * w2 + z3 * w3;
+                        emit(RasterSample { x, y, sample: index, depth, weights: (w1, w2, w3) });
+                    }
                 }
             }
         }
+    }
+
+    /// Distancia en píxeles de una muestra a la arista más cercana del
+    /// triángulo: cada peso baricéntrico por la altura sobre su arista opuesta
+    pub fn edge_distance(&self, sample: &RasterSample) -> f32 {
+        let p1 = self.v1.transformed_position.xy();
+        let p2 = self.v2.transformed_position.xy();
+        let p3 = self.v3.transformed_position.xy();
+
+        let double_area = ((p2 - p1).perp(&(p3 - p1))).abs();
+        let (w1, w2, w3) = sample.weights;
 
-        fragments
+        [(w1, p3 - p2), (w2, p1 - p3), (w3, p2 - p1)]
+            .iter()
+            .map(|(weight, edge)| weight * double_area / edge.magnitude().max(1e-6))
+            .fold(f32::INFINITY, f32::min)
     }
+
+    /// Construye el fragmento de una muestra interpolando los varyings
+    pub fn fragment(&self, sample: &RasterSample) -> Fragment {
+        let (w1, w2, w3) = sample.weights;
+
+        // Los atributos se interpolan como a/w y se dividen por 1/w
+        let p1 = w1 * (1.0 / self.v1.clip_position.w);
+        let p2 = w2 * (1.0 / self.v2.clip_position.w);
+        let p3 = w3 * (1.0 / self.v3.clip_position.w);
+        let inv_sum = 1.0 / (p1 + p2 + p3);
+
+        let varyings = Varyings::interpolate(
+            &self.v1.varyings,
+            &self.v2.varyings,
+            &self.v3.varyings,
+            p1 * inv_sum,
+            p2 * inv_sum,
+            p3 * inv_sum,
+        );
+
+        Fragment::from_varyings(varyings)
+    }
+}
+
+/// Muestra de un píxel cubierta por un triángulo, antes de interpolar atributos
+#[derive(Clone, Copy, Debug)]
+pub struct RasterSample {
+    pub x: i32,
+    pub y: i32,
+    /// Índice dentro del patrón de muestras del píxel
+    pub sample: usize,
+    pub depth: f32,
+    pub weights: (f32, f32, f32),
 }
 
-fn barycentric_coordinates(
-    px: f32, py: f32,
-    ax: f32, ay: f32,
-    bx: f32, by: f32,
-    cx: f32, cy: f32,
-) -> (f32, f32, f32) {
-    let denom = (by - cy) * (ax - cx) + (cx - bx) * (ay - cy);
+/// Arista de un triángulo en pantalla para el test de cobertura
+#[derive(Clone, Copy)]
+struct Edge {
+    from: Vec2,
+    to: Vec2,
+    /// Multiplica la función de arista para que sea positiva hacia el interior
+    sign: f32,
+    /// Si una muestra justo sobre la arista cuenta como cubierta
+    inclusive: bool,
+}
 
-    if denom.abs() < 1e-10 {
-        return (-1.0, -1.0, -1.0);
+impl Edge {
+    /// Arista `a`→`b` de un triángulo cuya área con signo tiene el signo `orientation`
+    fn new(a: Vec2, b: Vec2, orientation: f32) -> Self {
+        // Regla top-left (Y crece hacia abajo): las muestras sobre la arista
+        // solo son de este triángulo si el interior queda a la derecha (arista
+        // izquierda) o debajo de una arista horizontal (superior). El vecino
+        // que la comparte ve el interior del otro lado y no las toma.
+        let inward = Vec2::new(a.y - b.y, b.x - a.x) * orientation;
+        let inclusive = inward.x > 0.0 || (inward.x == 0.0 && inward.y > 0.0);
+
+        // Los extremos se guardan siempre en el mismo orden: los dos triángulos
+        // de una arista compartida calculan exactamente el mismo valor, con
+        // signos opuestos, y ninguna muestra queda en los dos o en ninguno
+        if (a.x, a.y) <= (b.x, b.y) {
+            Edge { from: a, to: b, sign: orientation, inclusive }
+        } else {
+            Edge { from: b, to: a, sign: -orientation, inclusive }
+        }
     }
 
-    let w1 = ((by - cy) * (px - cx) + (cx - bx) * (py - cy)) / denom;
-    let w2 = ((cy - ay) * (px - cx) + (ax - cx) * (py - cy)) / denom;
-    let w3 = 1.0 - w1 - w2;
+    /// Doble del área del triángulo que forman la arista y `point`,
+    /// positiva del lado interior
+    fn evaluate(&self, point: Vec2) -> f32 {
+        let value = (self.to.x - self.from.x) * (point.y - self.from.y)
+            - (self.to.y - self.from.y) * (point.x - self.from.x);
+        value * self.sign
+    }
 
-    (w1, w2, w3)
-}
\ No newline at end of file
+    fn covers(&self, value: f32) -> bool {
+        value > 0.0 || (value == 0.0 && self.inclusive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::antialiasing::sample_pattern;
+    use nalgebra_glm::Vec3;
+
+    fn vertex(x: f32, y: f32) -> Vertex {
+        Vertex::new(Vec3::new(x, y, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec2::zeros())
+    }
+
+    /// Una malla de 2×2 cuadrados de 4 píxeles con los vértices en centros de
+    /// píxel, así las aristas horizontales, verticales y diagonales pasan justo
+    /// por muestras. La columna derecha va en el orden de vértices contrario.
+    #[test]
+    fn shared_edges_are_covered_exactly_once() {
+        let mut coverage = [[0; 10]; 10];
+
+        for j in 0..2 {
+            for i in 0..2 {
+                let (x0, y0) = (0.5 + 4.0 * i as f32, 0.5 + 4.0 * j as f32);
+                let (x1, y1) = (x0 + 4.0, y0 + 4.0);
+                let mut triangles = [
+                    [vertex(x0, y0), vertex(x1, y0), vertex(x1, y1)],
+                    [vertex(x0, y0), vertex(x1, y1), vertex(x0, y1)],
+                ];
+                if i == 1 {
+                    triangles.iter_mut().for_each(|triangle| triangle.reverse());
+                }
+
+                for [v1, v2, v3] in triangles {
+                    let triangle = Triangle::new_from_vertices(v1, v2, v3);
+                    triangle.rasterize((0, 0, 9, 9), sample_pattern(1), |sample| {
+                        coverage[sample.y as usize][sample.x as usize] += 1;
+                    });
+                }
+            }
+        }
+
+        // Las aristas izquierda y superior del borde se incluyen; la derecha y la inferior no
+        for (y, row) in coverage.iter().enumerate() {
+            for (x, &count) in row.iter().enumerate() {
+                let expected = usize::from(x < 8 && y < 8);
+                assert_eq!(count, expected, "píxel ({}, {})", x, y);
+            }
+        }
+    }
+}
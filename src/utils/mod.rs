@@ -1,5 +1,3 @@
 pub mod collision;
-pub mod safe_zone; 
 
 pub use collision::{check_sphere_collision, resolve_sphere_collision};
-pub use safe_zone::SafeZone; 
\ No newline at end of file
@@ -0,0 +1,126 @@
+use nalgebra_glm::{Vec2, Vec3};
+use crate::color::Color;
+
+/// Cantidad máxima de floats que un vértice puede pasar al fragment shader
+pub const MAX_VARYINGS: usize = 20;
+
+// Distribución estándar que llena `vertex_shader`. Un shader que necesite
+// más datos puede escribir a partir de `CUSTOM`.
+pub const WORLD_POSITION: usize = 0;
+pub const NORMAL: usize = 3;
+pub const TEX_COORDS: usize = 6;
+pub const COLOR: usize = 8;
+/// Posición en espacio de objeto (para los mapas de superficie, que giran con el cuerpo)
+pub const OBJECT_POSITION: usize = 11;
+pub const CUSTOM: usize = 14;
+
+// Tiene que quedar lugar para al menos un vec3 propio
+const _: () = assert!(CUSTOM + 3 <= MAX_VARYINGS);
+
+/// Atributos de vértice que el rasterizador interpola hacia cada fragmento.
+/// Solo se interpolan los primeros `len` valores.
+#[derive(Clone, Copy, Debug)]
+pub struct Varyings {
+    pub values: [f32; MAX_VARYINGS],
+    pub len: usize,
+}
+
+impl Varyings {
+    pub fn new() -> Self {
+        Varyings {
+            values: [0.0; MAX_VARYINGS],
+            len: 0,
+        }
+    }
+
+    pub fn set(&mut self, slot: usize, value: f32) {
+        self.values[slot] = value;
+        self.len = self.len.max(slot + 1);
+    }
+
+    pub fn get(&self, slot: usize) -> f32 {
+        self.values[slot]
+    }
+
+    pub fn set_vec2(&mut self, slot: usize, value: Vec2) {
+        self.set(slot, value.x);
+        self.set(slot + 1, value.y);
+    }
+
+    pub fn get_vec2(&self, slot: usize) -> Vec2 {
+        Vec2::new(self.get(slot), self.get(slot + 1))
+    }
+
+    pub fn set_vec3(&mut self, slot: usize, value: Vec3) {
+        self.set(slot, value.x);
+        self.set(slot + 1, value.y);
+        self.set(slot + 2, value.z);
+    }
+
+    pub fn get_vec3(&self, slot: usize) -> Vec3 {
+        Vec3::new(self.get(slot), self.get(slot + 1), self.get(slot + 2))
+    }
+
+    /// Guarda un color como floats en [0, 1]
+    pub fn set_color(&mut self, slot: usize, color: Color) {
+        self.set_vec3(slot, Vec3::new(
+            color.r as f32 / 255.0,
+            color.g as f32 / 255.0,
+            color.b as f32 / 255.0,
+        ));
+    }
+
+    pub fn get_color(&self, slot: usize) -> Color {
+        let c = self.get_vec3(slot);
+        Color::from_float(c.x, c.y, c.z)
+    }
+
+    /// Interpolación lineal (usada al recortar en clip space)
+    pub fn lerp(&self, other: &Varyings, t: f32) -> Varyings {
+        let len = self.len.max(other.len);
+        let mut result = Varyings { values: [0.0; MAX_VARYINGS], len };
+        for i in 0..len {
+            result.values[i] = self.values[i] + (other.values[i] - self.values[i]) * t;
+        }
+        result
+    }
+
+    /// Combinación baricéntrica de tres vértices con pesos ya corregidos
+    pub fn interpolate(a: &Varyings, b: &Varyings, c: &Varyings, w1: f32, w2: f32, w3: f32) -> Varyings {
+        let len = a.len.max(b.len).max(c.len);
+        let mut result = Varyings { values: [0.0; MAX_VARYINGS], len };
+        for i in 0..len {
+            result.values[i] = a.values[i] * w1 + b.values[i] * w2 + c.values[i] * w3;
+        }
+        result
+    }
+}
+
+impl Default for Varyings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Un valor propio en `CUSTOM` se interpola igual que la distribución estándar
+    #[test]
+    fn custom_slots_are_interpolated() {
+        let mut a = Varyings::new();
+        a.set_vec3(WORLD_POSITION, Vec3::new(1.0, 2.0, 3.0));
+        let mut b = a;
+        let mut c = a;
+        a.set(CUSTOM, 0.0);
+        b.set(CUSTOM, 3.0);
+        c.set(CUSTOM, 6.0);
+
+        let result = Varyings::interpolate(&a, &b, &c, 0.5, 0.25, 0.25);
+        assert_eq!(result.len, CUSTOM + 1);
+        assert_eq!(result.get(CUSTOM), 2.25);
+        assert_eq!(result.get_vec3(WORLD_POSITION), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(a.lerp(&c, 0.5).get(CUSTOM), 3.0);
+    }
+}
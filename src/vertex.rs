@@ -1,5 +1,6 @@
-use nalgebra_glm::{Vec2, Vec3};
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 use crate::color::Color;
+use crate::varyings::Varyings;
 
 #[derive(Clone, Debug)]
 pub struct Vertex {
@@ -7,19 +8,25 @@ pub struct Vertex {
     pub normal: Vec3,
     pub tex_coords: Vec2,
     pub color: Color,
+    pub clip_position: Vec4,
     pub transformed_position: Vec3,
     pub transformed_normal: Vec3,
+    pub varyings: Varyings,
 }
 
 impl Vertex {
+    /// Vértice sin color propio: blanco, así `default_shader` muestra solo la
+    /// iluminación de la malla
     pub fn new(position: Vec3, normal: Vec3, tex_coords: Vec2) -> Self {
         Vertex {
             position,
             normal,
             tex_coords,
-            color: Color::black(),
+            color: Color::new(255, 255, 255),
+            clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
             transformed_position: position,
             transformed_normal: normal,
+            varyings: Varyings::new(),
         }
     }
 
@@ -29,14 +36,27 @@ impl Vertex {
             normal: Vec3::new(0.0, 0.0, 0.0),
             tex_coords: Vec2::new(0.0, 0.0),
             color,
+            clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
             transformed_position: Vec3::new(0.0, 0.0, 0.0),
             transformed_normal: Vec3::new(0.0, 0.0, 0.0),
+            varyings: Varyings::new(),
         }
     }
 
-    pub fn set_transformed(&mut self, position: Vec3, normal: Vec3) {
-        self.transformed_position = position;
-        self.transformed_normal = normal;
+    /// Interpola linealmente todos los atributos entre dos vértices
+    pub fn lerp(&self, other: &Vertex, t: f32) -> Vertex {
+        Vertex {
+            position: self.position + (other.position - self.position) * t,
+            normal: self.normal + (other.normal - self.normal) * t,
+            tex_coords: self.tex_coords + (other.tex_coords - self.tex_coords) * t,
+            color: self.color.lerp(&other.color, t),
+            clip_position: self.clip_position + (other.clip_position - self.clip_position) * t,
+            transformed_position: self.transformed_position
+                + (other.transformed_position - self.transformed_position) * t,
+            transformed_normal: self.transformed_normal
+                + (other.transformed_normal - self.transformed_normal) * t,
+            varyings: self.varyings.lerp(&other.varyings, t),
+        }
     }
 }
 
@@ -47,8 +67,10 @@ impl Default for Vertex {
             normal: Vec3::new(0.0, 1.0, 0.0),
             tex_coords: Vec2::new(0.0, 0.0),
             color: Color::black(),
+            clip_position: Vec4::new(0.0, 0.0, 0.0, 1.0),
             transformed_position: Vec3::new(0.0, 0.0, 0.0),
             transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+            varyings: Varyings::new(),
         }
     }
 }
\ No newline at end of file